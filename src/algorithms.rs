@@ -1,3 +1,23 @@
+/// implements `Guesser` for a `Suggester` that keeps track of the remaining words, every guess
+/// is its best suggestion
+macro_rules! guess_by_suggesting {
+    ($algorithm:ty) => {
+        impl crate::Guesser for $algorithm {
+            fn guess(&mut self, history: &[crate::Guess]) -> String {
+                crate::Suggester::suggest(self, history).word
+            }
+
+            fn try_guess(&mut self, history: &[crate::Guess]) -> Result<String, crate::Error> {
+                crate::Suggester::try_suggest(self, history).map(|s| s.word)
+            }
+
+            fn try_guess_remaining(&mut self, history: &[crate::Guess]) -> Result<(String, Option<usize>), crate::Error> {
+                crate::Suggester::try_suggest(self, history).map(|s| (s.word, Some(s.remaining)))
+            }
+        }
+    };
+}
+
 mod unoptimized;
 mod allocs;
 mod vecrem;
@@ -5,6 +25,11 @@ mod once_init;
 mod precalc;
 mod weight;
 mod prune;
+mod expected_turns;
+mod lookahead;
+mod minimax;
+mod optimal;
+mod scoring;
 
 pub use unoptimized::Unoptimized;
 pub use allocs::Allocs;
@@ -12,4 +37,278 @@ pub use vecrem::Vecrem;
 pub use once_init::OnceInit;
 pub use precalc::PreCalc;
 pub use weight::Weight;
-pub use prune::Prune;
\ No newline at end of file
+pub use prune::{Prune, Scoring};
+pub use expected_turns::{ExpectedTurns, TurnsEstimator};
+pub use lookahead::Lookahead;
+pub use minimax::Minimax;
+pub use optimal::{DecisionTree, Optimal};
+
+use std::borrow::Cow;
+use std::ops::Deref;
+use std::str::FromStr;
+use anyhow::anyhow;
+use crate::{Guess, PatternId, Prior, Suggestion, WordList};
+use crate::word_list::Word;
+
+/// The first word a guessing algorithm guesses
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Opener {
+    /// always open with the given word
+    Word(String),
+    /// score every word in the dictionary, just like any later guess, and open with the best.
+    /// This runs the full entropy scoring of the dictionary against itself
+    Compute,
+}
+
+impl Default for Opener {
+    fn default() -> Self {
+        Opener::Word("tares".to_string())
+    }
+}
+
+impl FromStr for Opener {
+    type Err = anyhow::Error;
+
+    /// parses `compute` as `Opener::Compute`, anything else must be a five letter word
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_ascii_lowercase();
+        if s == "compute" {
+            Ok(Opener::Compute)
+        } else if s.len() == 5 && s.bytes().all(|b| b.is_ascii_lowercase()) {
+            Ok(Opener::Word(s))
+        } else {
+            Err(anyhow!("an opener must be a five letter word or 'compute', got '{}'", s))
+        }
+    }
+}
+
+/// The words a guessing algorithm may choose its guesses from
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum GuessPool {
+    /// only guess words that could still be the answer
+    Remaining,
+    /// guess any word of the dictionary. A word that can no longer be the answer may be guessed
+    /// as a "probe" when it splits the remaining words better than any of them, but a remaining
+    /// word is preferred when the scores are equal, since it could win immediately
+    Dictionary,
+    /// like `Dictionary`, but only guess words that follow the NYT hard mode rules: every green
+    /// letter of the previous guesses is reused in place and every yellow letter is reused
+    Hard,
+}
+
+/// The words that could still be the answer, along with their prior weight. Shared by the
+/// algorithms that prune the words after every guess, it derefs to the words that are left
+#[derive(Clone)]
+pub(crate) struct Remaining {
+    /// the words that could be the answer, the whole dictionary or only the answers
+    // Cow is used because we are either going to be borrowing a Dictionary or we are going to
+    // own a dictionary once we start pruning words
+    words: Cow<'static, [Word]>,
+    /// the dictionary, the words are taken from
+    dictionary: &'static WordList,
+    /// how likely each word is to be the answer before the first guess
+    prior: Prior,
+    /// the sum of the weights of every word that could be the answer before the first guess
+    total: f64,
+}
+
+impl Remaining {
+    /// every word of `dictionary` could be the answer, weighed by its occurrence count
+    pub(crate) fn new(dictionary: &'static WordList) -> Self {
+        Self {
+            words: Cow::Borrowed(dictionary.indexed()),
+            dictionary,
+            prior: Prior::Count,
+            total: dictionary.total() as f64,
+        }
+    }
+
+    /// restricts the words that could be the answer to the words of `answers`, keeping the
+    /// weights the prior gives them in the dictionary. Answers that are not in the dictionary
+    /// are ignored
+    pub(crate) fn restrict(&mut self, answers: &WordList) {
+        let dictionary = self.dictionary.indexed();
+        self.words = Cow::Owned(answers
+            .words()
+            .filter_map(|word| self.dictionary.index_of(word))
+            .map(|index| dictionary[index])
+            .collect());
+        self.reweigh();
+    }
+
+    /// sets how likely each word is to be the answer. The weights are always computed over the
+    /// whole dictionary, even if the answers were restricted
+    pub(crate) fn set_prior(&mut self, prior: Prior) {
+        self.prior = prior;
+        self.reweigh();
+    }
+
+    /// sets the weight of every word from the prior, and the total weight of them all. The
+    /// weights are always recomputed, so that a later prior replaces an earlier one
+    fn reweigh(&mut self) {
+        let weights = self.prior.weights(self.dictionary);
+        for w in self.words.to_mut() {
+            w.weight = weights[w.index];
+        }
+        self.total = self.words.iter().map(|w| w.weight).sum();
+    }
+
+    /// prunes the words by only keeping the words that could be a possible match with the
+    /// `last_guess`
+    pub(crate) fn prune(&mut self, last_guess: &Guess) {
+        if matches!(self.words, Cow::Owned(_)) {
+            // if the remaining Vec is already owned, mutate it to retain the matching words
+            self.words
+                .to_mut()
+                .retain(|w| last_guess.matches(w.word));
+        } else {
+            // else, create a new owned Vec, but first, filter the matching words
+            self.words = Cow::Owned(self.words
+                .iter()
+                .filter(|w| last_guess.matches(w.word))
+                .copied()
+                .collect());
+        }
+    }
+
+    /// returns the sum of the weights of every word that could be the answer before the first
+    /// guess
+    pub(crate) fn total(&self) -> f64 {
+        self.total
+    }
+
+    /// returns the sum of the weights of the words that are left
+    pub(crate) fn weight(&self) -> f64 {
+        self.words.iter().map(|w| w.weight).sum()
+    }
+}
+
+impl Deref for Remaining {
+    type Target = [Word];
+
+    fn deref(&self) -> &[Word] {
+        &self.words
+    }
+}
+
+/// returns the words of `words` that may be guessed from `pool`, when the `remaining` words
+/// could still be the answer. The remaining words always come first, so that they win ties
+/// against probe words, followed by all other dictionary words, or only the ones that use every
+/// hint of the `history` in hard mode.
+/// Nothing is left to guess when no word could be the answer, whatever the pool, since the
+/// `history` then contradicts itself
+pub(crate) fn guess_pool<'a>(
+    pool: GuessPool,
+    words: &WordList,
+    remaining: &'a [Word],
+    history: &[Guess],
+) -> Cow<'a, [Word]> {
+    if pool == GuessPool::Remaining || remaining.is_empty() {
+        return Cow::Borrowed(remaining);
+    }
+    let dictionary = words.indexed();
+    let mut is_remaining = vec![false; dictionary.len()];
+    for w in remaining {
+        is_remaining[w.index] = true;
+    }
+    let hard = pool == GuessPool::Hard;
+    Cow::Owned(remaining
+        .iter()
+        .chain(dictionary.iter().filter(|w| {
+            !is_remaining[w.index] && (!hard || Guess::check_hard_mode(history, w.word).is_ok())
+        }))
+        .copied()
+        .collect())
+}
+
+/// inserts `candidate` into `top`, a list of at most `k` candidates sorted by descending
+/// `goodness`. A candidate is placed after any candidates with an equal goodness, so ties are won
+/// by whichever candidate was inserted first. Candidates that do not make the top `k` are dropped
+pub(crate) fn rank_insert<T>(top: &mut Vec<T>, k: usize, candidate: T, goodness: impl Fn(&T) -> f64) {
+    let score = goodness(&candidate);
+    let pos = top.partition_point(|c| goodness(c) >= score);
+    if pos < k {
+        top.insert(pos, candidate);
+        top.truncate(k);
+    }
+}
+
+/// computes the `Suggestion` details of guessing `word` when any of the `(word, weight)` pairs in
+/// `remaining` could be the answer, and `total` is the sum of the weights of the dictionary.
+/// The suggestion's goodness is its entropy bits.
+///
+/// This is used to describe a hardcoded guess, such as the opening word, that was not scored
+/// by an algorithm.
+pub(crate) fn suggestion_for<'a>(
+    word: &str,
+    remaining: impl Iterator<Item=(&'a str, f64)>,
+    total: f64,
+) -> Suggestion {
+    let mut buckets = [0.0; PatternId::COUNT];
+    let mut remaining_words = 0;
+    let mut remaining_weight = 0.0;
+    let mut word_weight = 0.0;
+    for (candidate, weight) in remaining {
+        buckets[PatternId::compute(candidate, word).index()] += weight;
+        remaining_words += 1;
+        remaining_weight += weight;
+        if candidate == word {
+            word_weight = weight;
+        }
+    }
+    let bits = scoring::entropy(&buckets, remaining_weight);
+    Suggestion {
+        word: word.to_string(),
+        bits,
+        goodness: bits,
+        p_word: word_weight / remaining_weight,
+        remaining: remaining_words,
+        remaining_mass: remaining_weight / total,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Prior, WordList};
+    use super::{rank_insert, Opener, Remaining};
+
+    #[test]
+    fn parse_opener() {
+        assert_eq!("salet".parse::<Opener>().unwrap(), Opener::Word("salet".to_string()));
+        assert_eq!("CRANE".parse::<Opener>().unwrap(), Opener::Word("crane".to_string()));
+        assert_eq!("compute".parse::<Opener>().unwrap(), Opener::Compute);
+        assert!("cranes".parse::<Opener>().is_err());
+        assert!("cr4ne".parse::<Opener>().is_err());
+    }
+
+    #[test]
+    fn rank_insert_keeps_top_k_in_order() {
+        let mut top = Vec::new();
+        for (i, score) in [3.0, 1.0, 4.0, 1.0, 5.0, 4.0].into_iter().enumerate() {
+            rank_insert(&mut top, 3, (i, score), |&(_, s)| s);
+        }
+        // the second 4.0 ties with the first, which was inserted earlier and stays ahead
+        assert_eq!(top, vec![(4, 5.0), (2, 4.0), (5, 4.0)]);
+
+        let mut none = Vec::new();
+        rank_insert(&mut none, 0, 1.0, |&s| s);
+        assert!(none.is_empty());
+    }
+
+    #[test]
+    fn later_prior_replaces_earlier_one() {
+        let words = WordList::dictionary();
+        let mut remaining = Remaining::new(words);
+        remaining.set_prior(Prior::SIGMOID);
+        remaining.set_prior(Prior::Count);
+        let counts = Remaining::new(words);
+        assert!(remaining.iter().zip(counts.iter()).all(|(a, b)| a.weight == b.weight));
+        assert_eq!(remaining.total(), counts.total());
+
+        // the restricted words are reweighed too
+        remaining.restrict(WordList::answers());
+        remaining.set_prior(Prior::Uniform);
+        assert!(remaining.iter().all(|w| w.weight == 1.0));
+        assert_eq!(remaining.total(), remaining.len() as f64);
+    }
+}
@@ -2,36 +2,47 @@
 //! algorithm:
 //! 1. it iterates over bytes instead of UTF-8 codepoints
 //! 2. it removes repeated .to_string() calls in the inner loop of the guess function and
-//! replaces it with Cow::Borrowed()
+//!    replaces it with Cow::Borrowed()
 //!
 use std::borrow::Cow;
 use std::collections::HashMap;
-use crate::{Guesser, Guess, DICTIONARY, Correctness};
+use super::Opener;
+use crate::{Error, Guesser, Guess, WordList, Correctness, Suggester, Suggestion};
 
 pub struct Allocs {
     /// a map containing all possible words that could be a possible solution
     /// it maps a `word` -> `occurrence count`, where occurrence_count is the number of times
     /// that word appeared in books
     remaining: HashMap<&'static str, usize>,
+    /// the dictionary, the words are taken from
+    words: &'static WordList,
+    /// the first word guessed
+    opener: Opener,
 }
 
 impl Allocs {
 
     /// Creates a new Allocs algorithm for solving wordle
-    pub fn new() -> Self {
+    pub fn new(words: &'static WordList) -> Self {
         Self {
-            remaining: HashMap::from_iter(
-                DICTIONARY
-                    .lines()
-                    .map(|line| {
-                        let (word, count) = line
-                            .split_once(' ')
-                            .expect("every line is a word + space + occurrence_count");
-                        let count: usize = count.parse().expect("every count is a number");
-                        (word, count)
-                    })),
+            remaining: HashMap::from_iter(words.entries().iter().copied()),
+            words,
+            opener: Opener::default(),
         }
     }
+
+    /// sets the first word to guess, the default is "tares"
+    pub fn with_opener(mut self, opener: Opener) -> Self {
+        self.opener = opener;
+        self
+    }
+}
+
+impl Default for Allocs {
+    /// creates the algorithm for the embedded dictionary
+    fn default() -> Self {
+        Self::new(WordList::dictionary())
+    }
 }
 
 /// Holds the details of a potential best guess
@@ -41,28 +52,47 @@ struct Candidate {
     word: &'static str,
     /// the candidates 'goodness' score, or entropy 'bits'. Higher is better
     goodness: f64,
+    /// the expected amount of information, in bits, gained by guessing the word
+    bits: f64,
+    /// the probability that the word is the answer
+    p_word: f64,
 }
 
 impl Guesser for Allocs {
-
     fn guess(&mut self, history: &[Guess]) -> String {
+        self.suggest(history).word
+    }
+
+    fn try_guess(&mut self, history: &[Guess]) -> Result<String, Error> {
+        self.try_suggest(history).map(|s| s.word)
+    }
+
+    fn try_guess_remaining(&mut self, history: &[Guess]) -> Result<(String, Option<usize>), Error> {
+        self.try_suggest(history).map(|s| (s.word, Some(s.remaining)))
+    }
+}
+
+impl Suggester for Allocs {
+    fn rank(&mut self, history: &[Guess], k: usize) -> Vec<Suggestion> {
 
         // prune the dictionary by only keeping words that could be a possible match
         if let Some(last) = history.last() {
             self.remaining.retain(|&word, _| last.matches(word));
         }
 
-        // hardcode the first guess to "tares"
+        // the first guess is either a fixed opener, or it is scored like any other guess
         if history.is_empty() {
-            return "tares".to_string();
+            if let Opener::Word(word) = &self.opener {
+                return vec![super::suggestion_for(word, self.remaining.iter().map(|(&w, &c)| (w, c as f64)), self.words.total() as f64)];
+            }
         }
 
         // the sum of the counts of all the remaining words in the dictionary
         let remaining_count: usize = self.remaining.iter().map(|(_, &c)| c).sum();
-        // the best word
-        let mut best: Option<Candidate> = None;
+        // the best k words, best first
+        let mut top: Vec<Candidate> = Vec::with_capacity(k + 1);
 
-        for &word in self.remaining.keys() {
+        for (&word, &count) in &self.remaining {
             let mut sum = 0.0;
 
             for pattern in Correctness::patterns() {
@@ -92,16 +122,22 @@ impl Guesser for Allocs {
                 sum += prob_of_this_pattern * prob_of_this_pattern.log2()
             }
             // negate the sum to get the final goodness amount, a.k.a the entropy "bits"
-            let goodness = -sum;
+            let bits = -sum;
+            let goodness = bits;
+            // the probability of the current word, used to report the suggestion
+            let p_word = count as f64 / remaining_count as f64;
 
-            if let Some(c) = best {
-                if goodness > c.goodness {
-                    best = Some(Candidate { word, goodness })
-                }
-            } else {
-                best = Some(Candidate { word, goodness })
-            }
+            super::rank_insert(&mut top, k, Candidate { word, goodness, bits, p_word }, |c| c.goodness);
         }
-        best.unwrap().word.to_string()
+        top.into_iter()
+            .map(|c| Suggestion {
+                word: c.word.to_string(),
+                bits: c.bits,
+                goodness: c.goodness,
+                p_word: c.p_word,
+                remaining: self.remaining.len(),
+                remaining_mass: remaining_count as f64 / self.words.total() as f64,
+            })
+            .collect()
     }
 }
\ No newline at end of file
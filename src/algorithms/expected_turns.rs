@@ -0,0 +1,400 @@
+//! A wordle solver algorithm that guesses the word which minimizes the expected total number of
+//! guesses, instead of mixing the chance of winning and the information gained into one score.
+//!
+//! Guessing a word `w` wins right away with probability `p_w`, otherwise the game goes on with
+//! some uncertainty left about the answer. If `f(H)` is the number of guesses it takes to solve a
+//! game with `H` bits of uncertainty left, the expected number of guesses is
+//!
+//! `E[w] = p_w * 1 + (1 - p_w) * (1 + f(H - bits_w))`
+//!
+//! where `H` is the uncertainty before guessing, and `bits_w` is the expected information gained
+//! by guessing `w`. The estimator `f` is learned from simulated games, see `TurnsEstimator`.
+//!
+use std::borrow::Cow;
+use super::{GuessPool, Opener, Prune, Remaining};
+use crate::{Error, GameRecord, Guess, WordList, PatternTable, Prior, Suggester, Suggestion, Wordle};
+use super::scoring;
+use crate::word_list::Word;
+
+/// Estimates how many more guesses it takes to solve a game from the uncertainty, in bits, that
+/// is left about the answer. Counts the guess of the answer itself, so a game with only one
+/// possible answer left takes one more guess
+#[derive(Debug, Clone, PartialEq)]
+pub struct TurnsEstimator {
+    /// `(bits, turns)` points, sorted by bits, that are linearly interpolated
+    points: Vec<(f64, f64)>,
+}
+
+impl TurnsEstimator {
+    /// the width, in bits, of the uncertainty bins that the samples are averaged over
+    const BIN_BITS: f64 = 0.5;
+
+    /// a bin needs at least this many samples to become a point
+    const MIN_SAMPLES: usize = 10;
+
+    /// learns the estimator from the `records` of games guessed from the words of `words`,
+    /// where every word could be the answer with a probability proportional to its weight under
+    /// `prior`. Every turn of a solved game is a sample of the uncertainty left before the turn,
+    /// and the number of guesses it then took to solve the game
+    pub fn learn(words: &'static WordList, prior: Prior, records: &[GameRecord]) -> Self {
+        let mut remaining = Remaining::new(words);
+        remaining.set_prior(prior);
+        Self::learn_from(&remaining, records)
+    }
+
+    /// learns the estimator from the `records` of games where the `answers` could be the answer
+    /// before the first guess, see `learn`
+    fn learn_from(answers: &[Word], records: &[GameRecord]) -> Self {
+        let mut samples = Vec::new();
+        for record in records.iter().filter(|r| r.solved()) {
+            let mut remaining = answers.to_vec();
+            for (i, turn) in record.turns.iter().enumerate() {
+                samples.push((scoring::uncertainty(&remaining), (record.turns.len() - i) as f64));
+                let guess = Guess { word: Cow::Borrowed(&turn.word), mask: turn.mask };
+                remaining.retain(|w| guess.matches(w.word));
+            }
+        }
+        Self::fit(&samples)
+    }
+
+    /// learns the estimator from the games of `wordle` that guessers created by `prune` play
+    /// against every word of `answers`. The guessers should be set up like the `ExpectedTurns`
+    /// the estimator is for, with the same prior, pool and answers, so that the estimator
+    /// describes the games it estimates. Returns an error if an answer is not in the dictionary
+    pub fn simulate(wordle: &Wordle, answers: &WordList, prune: impl Fn() -> Prune) -> Result<Self, Error> {
+        let records = answers
+            .words()
+            .map(|answer| wordle.try_record(answer, prune()))
+            .collect::<Result<Vec<GameRecord>, Error>>()?;
+        Ok(Self::learn_from(prune().remaining(), &records))
+    }
+
+    /// fits the estimator to `(bits, turns)` samples by averaging the samples of each
+    /// `BIN_BITS` wide bin of uncertainty. A single possible answer always takes one guess, and
+    /// more uncertainty never takes fewer guesses
+    pub fn fit(samples: &[(f64, f64)]) -> Self {
+        // (sum of bits, sum of turns, number of samples) of each bin
+        let mut bins: Vec<(f64, f64, usize)> = Vec::new();
+        for &(bits, turns) in samples.iter().filter(|&&(bits, _)| bits > 0.0) {
+            let bin = (bits / Self::BIN_BITS) as usize;
+            if bins.len() <= bin {
+                bins.resize(bin + 1, (0.0, 0.0, 0));
+            }
+            bins[bin].0 += bits;
+            bins[bin].1 += turns;
+            bins[bin].2 += 1;
+        }
+
+        let mut points = vec![(0.0, 1.0)];
+        for (bits, turns, n) in bins {
+            if n >= Self::MIN_SAMPLES {
+                let last = points[points.len() - 1].1;
+                points.push((bits / n as f64, (turns / n as f64).max(last)));
+            }
+        }
+        Self { points }
+    }
+
+    /// returns the expected number of guesses it takes to solve a game with `bits` of
+    /// uncertainty left. Past the last point the last segment is extended
+    pub fn turns(&self, bits: f64) -> f64 {
+        let bits = bits.max(0.0);
+        let points = &self.points;
+        if points.len() == 1 {
+            return points[0].1 + bits;
+        }
+        let i = points
+            .partition_point(|&(b, _)| b <= bits)
+            .clamp(1, points.len() - 1);
+        let ((b0, t0), (b1, t1)) = (points[i - 1], points[i]);
+        t0 + (t1 - t0) * (bits - b0) / (b1 - b0)
+    }
+}
+
+impl Default for TurnsEstimator {
+    /// the estimator `simulate` learns from the games the default `Prune` plays against the
+    /// embedded answers, guessing from the embedded dictionary, rounded to two decimals. It must be regenerated when `Prune` changes, which the ignored
+    /// `default_is_simulated` test checks
+    fn default() -> Self {
+        Self {
+            points: vec![
+                (0.0, 1.0),
+                (0.20, 1.27),
+                (0.78, 1.54),
+                (1.24, 1.89),
+                (1.73, 2.18),
+                (2.25, 2.38),
+                (2.69, 2.46),
+                (3.24, 2.79),
+                (3.69, 3.18),
+                (4.28, 3.24),
+                (4.70, 3.24),
+                (5.09, 3.29),
+                (6.03, 3.29),
+                (9.13, 3.96),
+            ],
+        }
+    }
+}
+
+pub struct ExpectedTurns {
+    /// all possible words that could be the answer, along with their prior weight
+    remaining: Remaining,
+    /// pre-computed patterns of the dictionary, if they were loaded
+    table: Option<&'static PatternTable>,
+    /// number of threads used to score candidate words
+    threads: usize,
+    /// the words that may be guessed
+    pool: GuessPool,
+    /// the dictionary, the words are taken from
+    words: &'static WordList,
+    /// estimates the guesses it takes to solve the game after a guess
+    estimator: TurnsEstimator,
+    /// the first word guessed
+    opener: Opener,
+}
+
+impl ExpectedTurns {
+    /// creates a new ExpectedTurns algo that guesses the words of `words`. The pattern table of
+    /// the words is used if it was loaded, see `WordList::load_table`
+    pub fn new(words: &'static WordList) -> Self {
+        Self {
+            remaining: Remaining::new(words),
+            table: words.table(),
+            threads: 1,
+            pool: GuessPool::Remaining,
+            words,
+            estimator: TurnsEstimator::default(),
+            opener: Opener::default(),
+        }
+    }
+
+    /// sets the first word to guess, the default is "tares"
+    pub fn with_opener(mut self, opener: Opener) -> Self {
+        self.opener = opener;
+        self
+    }
+
+    /// sets the number of threads used to score candidate words, the default is one
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// sets the words that may be guessed, the default is `GuessPool::Remaining`
+    pub fn with_pool(mut self, pool: GuessPool) -> Self {
+        self.pool = pool;
+        self
+    }
+
+    /// restricts the words that could be the answer to the words of `answers`, see
+    /// `Prune::with_answers`. By default every dictionary word could be the answer
+    pub fn with_answers(mut self, answers: &'static WordList) -> Self {
+        self.remaining.restrict(answers);
+        self
+    }
+
+    /// sets how likely each word is to be the answer, the default is `Prior::Count`. The
+    /// estimator should be learned with the same prior, see `with_estimator`
+    pub fn with_prior(mut self, prior: Prior) -> Self {
+        self.remaining.set_prior(prior);
+        self
+    }
+
+    /// sets the estimator of the guesses it takes to solve a game from its uncertainty, the
+    /// default was learned from games played with the occurrence count prior
+    pub fn with_estimator(mut self, estimator: TurnsEstimator) -> Self {
+        self.estimator = estimator;
+        self
+    }
+
+    /// returns the expected number of guesses of a game, counting from the next guess, when the
+    /// next guess is the answer with probability `p_word` and otherwise leaves `bits` of
+    /// uncertainty
+    fn expected_turns(&self, p_word: f64, bits: f64) -> f64 {
+        p_word + (1.0 - p_word) * (1.0 + self.estimator.turns(bits))
+    }
+}
+
+impl Default for ExpectedTurns {
+    /// creates the algorithm for the embedded dictionary
+    fn default() -> Self {
+        Self::new(WordList::dictionary())
+    }
+}
+
+guess_by_suggesting!(ExpectedTurns);
+
+impl Suggester for ExpectedTurns {
+    fn rank(&mut self, history: &[Guess], k: usize) -> Vec<Suggestion> {
+        // prune the dictionary by only keeping words that could be a possible match
+        if let Some(last) = history.last() {
+            self.remaining.prune(last);
+        }
+
+        // the uncertainty about the answer, before guessing
+        let uncertainty = scoring::uncertainty(&self.remaining);
+
+        // the first guess is either a fixed opener, or it is scored like any other guess
+        if history.is_empty() {
+            if let Opener::Word(word) = &self.opener {
+                let s = super::suggestion_for(word, self.remaining.iter().map(|w| (w.word, w.weight)), self.remaining.total());
+                let turns = self.expected_turns(s.p_word, uncertainty - s.bits);
+                return vec![Suggestion { goodness: -turns, ..s }];
+            }
+        }
+
+        // the sum of the weights of all the remaining words in the dictionary
+        let remaining_weight = self.remaining.weight();
+        let remaining = &self.remaining;
+        let table = self.table;
+        let guesses = super::guess_pool(self.pool, self.words, remaining, history);
+        // the probability of the guess at index `i` being the answer, probe words can't be
+        let p_word = |i: usize, word: &Word| {
+            if i < remaining.len() {
+                word.weight / remaining_weight
+            } else {
+                0.0
+            }
+        };
+        // the fewer guesses a word is expected to take the better, rounded like bits so that
+        // words which split the remaining words the same way compare as equal
+        let top = scoring::top_by(&guesses, self.threads, k, |i, word| {
+            let buckets = scoring::buckets(table, word, remaining);
+            let bits = scoring::entropy(&buckets, remaining_weight);
+            -scoring::round_bits(self.expected_turns(p_word(i, word), uncertainty - bits))
+        });
+        top.into_iter()
+            .map(|(i, goodness)| {
+                let word = guesses[i];
+                // only the goodness is kept while scoring, recompute the bits of the winners
+                let buckets = scoring::buckets(table, &word, remaining);
+                Suggestion {
+                    word: word.word.to_string(),
+                    bits: scoring::entropy(&buckets, remaining_weight),
+                    goodness,
+                    p_word: p_word(i, &word),
+                    remaining: remaining.len(),
+                    remaining_mass: remaining_weight / self.remaining.total(),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+    use std::time::Duration;
+    use crate::{Correctness, GameRecord, Guess, Outcome, Prior, Suggester, Turn, WordList, Wordle};
+    use crate::algorithms::{scoring, Prune};
+    use super::{ExpectedTurns, TurnsEstimator};
+
+    /// returns the turn of guessing `word` when the answer is `answer`
+    fn turn(word: &str, answer: &str) -> Turn {
+        Turn {
+            word: word.to_string(),
+            mask: Correctness::compute(answer, word),
+            remaining: None,
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    #[test]
+    fn fit_averages_bins() {
+        let mut samples = vec![(0.0, 1.0); 20];
+        samples.extend(vec![(1.1, 2.0); 10]);
+        samples.extend(vec![(1.3, 3.0); 10]);
+        // too few samples to make a point
+        samples.extend(vec![(2.1, 9.0); 3]);
+        samples.extend(vec![(4.0, 4.0); 10]);
+        let estimator = TurnsEstimator::fit(&samples);
+        assert_eq!(estimator.points.len(), 3);
+        assert_eq!(estimator.points[0], (0.0, 1.0));
+        assert!((estimator.points[1].0 - 1.2).abs() < 1e-12);
+        assert!((estimator.points[1].1 - 2.5).abs() < 1e-12);
+
+        assert_eq!(estimator.turns(0.0), 1.0);
+        assert_eq!(estimator.turns(-0.5), 1.0);
+        assert!((estimator.turns(0.6) - 1.75).abs() < 1e-12);
+        assert!((estimator.turns(4.0) - 4.0).abs() < 1e-12);
+        // the last segment is extended
+        assert!((estimator.turns(6.8) - 5.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn more_uncertainty_never_takes_fewer_turns() {
+        let mut samples = vec![(1.0, 3.0); 10];
+        samples.extend(vec![(2.0, 2.0); 10]);
+        let estimator = TurnsEstimator::fit(&samples);
+        assert!(estimator.turns(2.0) >= estimator.turns(1.0));
+
+        let default = TurnsEstimator::default();
+        assert!(default.points.windows(2).all(|p| p[0].0 < p[1].0 && p[0].1 <= p[1].1));
+    }
+
+    #[test]
+    fn learn_samples_every_turn_of_solved_games() {
+        // 16 words that only differ in their last letter, with different counts
+        let text: String = ('a'..='p').enumerate().map(|(i, c)| format!("abcd{} {}\n", c, i + 1)).collect();
+        let words: &'static WordList = Box::leak(Box::new(WordList::parse("words", String::leak(text)).unwrap()));
+
+        // abcda leaves the 15 other words, then the answer is guessed
+        let mut records: Vec<GameRecord> = ('b'..='k')
+            .map(|c| {
+                let answer: &'static str = String::leak(format!("abcd{}", c));
+                GameRecord { answer, turns: vec![turn("abcda", answer), turn(answer, answer)], outcome: Outcome::Won(2) }
+            })
+            .collect();
+        // unsolved games are not learned from
+        records.push(GameRecord { answer: "abcdp", turns: vec![turn("abcda", "abcdp"); 12], outcome: Outcome::Unsolved });
+
+        // every word is equally likely, whatever its count
+        let estimator = TurnsEstimator::learn(words, Prior::Uniform, &records);
+        let expected = [(0.0, 1.0), (15f64.log2(), 1.0), (4.0, 2.0)];
+        assert_eq!(estimator.points.len(), expected.len());
+        for (p, e) in estimator.points.iter().zip(expected) {
+            assert!((p.0 - e.0).abs() < 1e-12 && p.1 == e.1, "{:?} != {:?}", p, e);
+        }
+    }
+
+    #[test]
+    #[ignore = "plays every answer, run with --release --ignored"]
+    fn default_is_simulated() {
+        let words = WordList::dictionary();
+        let simulated = TurnsEstimator::simulate(&Wordle::new(words), WordList::answers(), || Prune::new(words)).unwrap();
+        let default = TurnsEstimator::default();
+        assert_eq!(default.points.len(), simulated.points.len());
+        for (d, s) in default.points.iter().zip(&simulated.points) {
+            assert!((d.0 - s.0).abs() <= 0.005 + 1e-9 && (d.1 - s.1).abs() <= 0.005 + 1e-9, "{:?} != {:?}", d, s);
+        }
+    }
+
+    #[test]
+    fn ranks_by_the_expected_turns() {
+        let words = WordList::dictionary();
+        let history = [Guess {
+            word: Cow::Borrowed("tares"),
+            mask: Correctness::try_from_str("wwcww").unwrap(),
+        }];
+        let mut expected_turns = ExpectedTurns::new(words);
+        let ranked = expected_turns.rank(&history, 5);
+        assert_eq!(ranked.len(), 5);
+        assert!(ranked.windows(2).all(|s| s[0].goodness >= s[1].goodness));
+
+        // the goodness is the negated expected number of guesses, counting the next one
+        let uncertainty = scoring::uncertainty(&expected_turns.remaining);
+        for s in &ranked {
+            let turns = expected_turns.expected_turns(s.p_word, uncertainty - s.bits);
+            assert!((s.goodness + turns).abs() < 1e-6, "{}: {} != {}", s.word, s.goodness, -turns);
+            assert!(s.goodness <= -1.0);
+        }
+        // no remaining word is expected to take fewer guesses than the best one
+        let total = expected_turns.remaining.weight();
+        for word in expected_turns.remaining.iter() {
+            let bits = scoring::entropy(&scoring::buckets(None, word, &expected_turns.remaining), total);
+            let turns = expected_turns.expected_turns(word.weight / total, uncertainty - bits);
+            assert!(-turns <= ranked[0].goodness + 1e-9, "{}", word.word);
+        }
+    }
+}
@@ -0,0 +1,230 @@
+//! A wordle solver algorithm that looks two guesses ahead.
+//!
+//! Every other algorithm only scores the information gained by the next guess. `Lookahead`
+//! first scores every word like `Prune` does, then for the `beam` best of them it splits the
+//! remaining words into the pattern buckets the word would produce, and finds the best entropy
+//! the following guess could gain within each bucket. Words are ranked by the two-step total:
+//!
+//! `bits_w + SUM_pat P(pat) * max_g bits_g(pat)`
+//!
+//! where `bits_w` is the information gained by guessing `w`, `P(pat)` is the probability of
+//! seeing the pattern `pat`, and `bits_g(pat)` is the information gained by then guessing `g`
+//! when only the words of the `pat` bucket are left. The beam width bounds the runtime, only
+//! `beam` words are looked ahead from.
+//!
+use std::borrow::Cow;
+use super::{GuessPool, Opener, Remaining};
+use crate::{Guess, WordList, PatternId, PatternTable, Prior, Suggester, Suggestion};
+use super::scoring;
+use crate::word_list::Word;
+
+pub struct Lookahead {
+    /// all possible words that could be the answer, along with their prior weight
+    remaining: Remaining,
+    /// pre-computed patterns of the dictionary, if they were loaded
+    table: Option<&'static PatternTable>,
+    /// number of threads used to score candidate words
+    threads: usize,
+    /// the words that may be guessed
+    pool: GuessPool,
+    /// the dictionary, the words are taken from
+    words: &'static WordList,
+    /// the number of best single-step words that are looked ahead from
+    beam: usize,
+    /// the first word guessed
+    opener: Opener,
+}
+
+impl Lookahead {
+    /// the default beam width
+    pub const DEFAULT_BEAM: usize = 10;
+
+    /// creates a new Lookahead algo that guesses the words of `words`. The pattern table of the
+    /// words is used if it was loaded, see `WordList::load_table`
+    pub fn new(words: &'static WordList) -> Self {
+        Self {
+            remaining: Remaining::new(words),
+            table: words.table(),
+            threads: 1,
+            pool: GuessPool::Remaining,
+            words,
+            beam: Self::DEFAULT_BEAM,
+            opener: Opener::default(),
+        }
+    }
+
+    /// sets the first word to guess, the default is "tares"
+    pub fn with_opener(mut self, opener: Opener) -> Self {
+        self.opener = opener;
+        self
+    }
+
+    /// sets the number of threads used to score candidate words, the default is one. The
+    /// words of the beam are looked ahead from in parallel
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// sets the words that may be guessed, the default is `GuessPool::Remaining`. The
+    /// following guess is chosen from the same pool
+    pub fn with_pool(mut self, pool: GuessPool) -> Self {
+        self.pool = pool;
+        self
+    }
+
+    /// restricts the words that could be the answer to the words of `answers`, see
+    /// `Prune::with_answers`. By default every dictionary word could be the answer
+    pub fn with_answers(mut self, answers: &'static WordList) -> Self {
+        self.remaining.restrict(answers);
+        self
+    }
+
+    /// sets how likely each word is to be the answer, the default is `Prior::Count`
+    pub fn with_prior(mut self, prior: Prior) -> Self {
+        self.remaining.set_prior(prior);
+        self
+    }
+
+    /// sets the number of best single-step words that are looked ahead from, the default is
+    /// `DEFAULT_BEAM`. At least as many words as are ranked are always looked ahead from
+    pub fn with_beam(mut self, beam: usize) -> Self {
+        self.beam = beam.max(1);
+        self
+    }
+
+    /// computes the expected entropy of the best guess after guessing `guess`, when the
+    /// `remaining` words, weighing `remaining_weight` in total, could be the answer
+    fn next_bits(&self, guess: &Word, remaining: &[Word], remaining_weight: f64, history: &[Guess]) -> f64 {
+        let mut buckets: Vec<Vec<Word>> = vec![Vec::new(); PatternId::COUNT];
+        for answer in remaining {
+            buckets[scoring::pattern(self.table, guess, answer).index()].push(*answer);
+        }
+
+        // the history that the guess of each bucket is pushed onto, for the hard mode rules
+        let mut history: Vec<Guess> = history
+            .iter()
+            .map(|g| Guess { word: Cow::Borrowed(&g.word), mask: g.mask })
+            .collect();
+        let mut sum = 0.0;
+        for (pattern, bucket) in buckets.iter().enumerate() {
+            // a single word is guessed next, nothing is left to learn
+            if bucket.len() < 2 {
+                continue;
+            }
+            let bucket_weight: f64 = bucket.iter().map(|w| w.weight).sum();
+            if bucket_weight == 0.0 {
+                continue;
+            }
+            let mask = PatternId::from_u8(pattern as u8).unwrap().into();
+            history.push(Guess { word: Cow::Borrowed(guess.word), mask });
+            let guesses = super::guess_pool(self.pool, self.words, bucket, &history);
+            history.pop();
+
+            let best = scoring::top_by(&guesses, 1, 1, |_, word| {
+                scoring::entropy(&scoring::buckets(self.table, word, bucket), bucket_weight)
+            });
+            if let Some(&(_, bits)) = best.first() {
+                sum += bucket_weight / remaining_weight * bits;
+            }
+        }
+        sum
+    }
+}
+
+impl Default for Lookahead {
+    /// creates the algorithm for the embedded dictionary
+    fn default() -> Self {
+        Self::new(WordList::dictionary())
+    }
+}
+
+guess_by_suggesting!(Lookahead);
+
+impl Suggester for Lookahead {
+    fn rank(&mut self, history: &[Guess], k: usize) -> Vec<Suggestion> {
+        // prune the dictionary by only keeping words that could be a possible match
+        if let Some(last) = history.last() {
+            self.remaining.prune(last);
+        }
+
+        // the first guess is either a fixed opener, or it is scored like any other guess
+        if history.is_empty() {
+            if let Opener::Word(word) = &self.opener {
+                return vec![super::suggestion_for(word, self.remaining.iter().map(|w| (w.word, w.weight)), self.remaining.total())];
+            }
+        }
+
+        // the sum of the weights of all the remaining words in the dictionary
+        let remaining_weight = self.remaining.weight();
+        let remaining = &self.remaining;
+        let table = self.table;
+        let guesses = super::guess_pool(self.pool, self.words, remaining, history);
+
+        // the best single-step words by their entropy bits, like `Prune` scores probe words
+        let beam = scoring::top_by(&guesses, self.threads, self.beam.max(k), |_, word| {
+            scoring::round_bits(scoring::entropy(&scoring::buckets(table, word, remaining), remaining_weight))
+        });
+        let beam_words: Vec<Word> = beam.iter().map(|&(i, _)| guesses[i]).collect();
+
+        // rank the beam by the two-step total, rounded so that ties keep the single-step order
+        let top = scoring::top_by(&beam_words, self.threads, k, |j, word| {
+            let bits = beam[j].1;
+            scoring::round_bits(bits + self.next_bits(word, remaining, remaining_weight, history))
+        });
+        top.into_iter()
+            .map(|(j, goodness)| {
+                let (i, bits) = beam[j];
+                let word = guesses[i];
+                Suggestion {
+                    word: word.word.to_string(),
+                    bits,
+                    goodness,
+                    // probe words can't be the answer
+                    p_word: if i < remaining.len() { word.weight / remaining_weight } else { 0.0 },
+                    remaining: remaining.len(),
+                    remaining_mass: remaining_weight / self.remaining.total(),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+    use crate::{Correctness, Guess, Suggester, WordList};
+    use super::Lookahead;
+
+    #[test]
+    fn two_step_total_includes_the_next_guess() {
+        let words = WordList::dictionary();
+        let history = [Guess {
+            word: Cow::Borrowed("tares"),
+            mask: Correctness::try_from_str("wwwww").unwrap(),
+        }];
+        let mut lookahead = Lookahead::new(words).with_beam(3);
+        let ranked = lookahead.rank(&history, 3);
+        assert_eq!(ranked.len(), 3);
+        assert!(ranked.windows(2).all(|s| s[0].goodness >= s[1].goodness));
+        // the following guess can only learn more, but never more than is left to learn
+        for s in &ranked {
+            assert!(s.goodness >= s.bits);
+            assert!(s.goodness <= (s.remaining as f64).log2() + 1e-9);
+        }
+    }
+
+    #[test]
+    fn beam_of_one_keeps_the_single_step_best() {
+        let words = WordList::dictionary();
+        let history = [Guess {
+            word: Cow::Borrowed("tares"),
+            mask: Correctness::try_from_str("cwwmw").unwrap(),
+        }];
+        let beam = Lookahead::new(words).with_beam(1).rank(&history, 1);
+        let wide = Lookahead::new(words).with_beam(20).rank(&history, 20);
+        let best_bits = wide.iter().map(|s| s.bits).fold(f64::MIN, f64::max);
+        assert_eq!(beam[0].bits, best_bits);
+        assert!(wide[0].goodness >= beam[0].goodness);
+    }
+}
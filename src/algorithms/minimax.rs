@@ -0,0 +1,188 @@
+//! A wordle solver algorithm that bounds the worst case instead of optimizing the average.
+//!
+//! Guessing a word splits the remaining words into pattern buckets, and the answer could be in
+//! any of them. `Minimax` guesses the word whose largest bucket holds the fewest words, so that
+//! even the most unlucky pattern leaves as few words as possible. Words with the same largest
+//! bucket are ranked by their entropy bits, like `Prune` ranks probe words.
+//!
+use super::{GuessPool, Opener, Remaining};
+use crate::{Guess, WordList, PatternId, PatternTable, Prior, Suggester, Suggestion};
+use super::scoring;
+use crate::word_list::Word;
+
+pub struct Minimax {
+    /// all possible words that could be the answer, along with their prior weight
+    remaining: Remaining,
+    /// pre-computed patterns of the dictionary, if they were loaded
+    table: Option<&'static PatternTable>,
+    /// number of threads used to score candidate words
+    threads: usize,
+    /// the words that may be guessed
+    pool: GuessPool,
+    /// the dictionary, the words are taken from
+    words: &'static WordList,
+    /// the first word guessed
+    opener: Opener,
+}
+
+impl Minimax {
+    /// entropy bits are always below this, `log2(PatternId::COUNT)` rounded up, so dividing
+    /// the bits by it keeps the entropy tie-breaker below one word of the largest bucket
+    const MAX_BITS: f64 = 8.0;
+
+    /// creates a new Minimax algo that guesses the words of `words`. The pattern table of the
+    /// words is used if it was loaded, see `WordList::load_table`
+    pub fn new(words: &'static WordList) -> Self {
+        Self {
+            remaining: Remaining::new(words),
+            table: words.table(),
+            threads: 1,
+            pool: GuessPool::Remaining,
+            words,
+            opener: Opener::default(),
+        }
+    }
+
+    /// sets the first word to guess, the default is "tares"
+    pub fn with_opener(mut self, opener: Opener) -> Self {
+        self.opener = opener;
+        self
+    }
+
+    /// sets the number of threads used to score candidate words, the default is one
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// sets the words that may be guessed, the default is `GuessPool::Remaining`. Probe words
+    /// usually split the remaining words more evenly, bounding the worst case better
+    pub fn with_pool(mut self, pool: GuessPool) -> Self {
+        self.pool = pool;
+        self
+    }
+
+    /// restricts the words that could be the answer to the words of `answers`, see
+    /// `Prune::with_answers`. By default every dictionary word could be the answer
+    pub fn with_answers(mut self, answers: &'static WordList) -> Self {
+        self.remaining.restrict(answers);
+        self
+    }
+
+    /// sets how likely each word is to be the answer, the default is `Prior::Count`. The prior
+    /// only changes the entropy tie-breaker, the largest bucket counts words
+    pub fn with_prior(mut self, prior: Prior) -> Self {
+        self.remaining.set_prior(prior);
+        self
+    }
+}
+
+/// returns the number of the `answers` in the largest pattern bucket if `guess` were guessed
+fn largest_bucket(table: Option<&PatternTable>, guess: &Word, answers: &[Word]) -> usize {
+    let mut counts = [0usize; PatternId::COUNT];
+    for answer in answers {
+        counts[scoring::pattern(table, guess, answer).index()] += 1;
+    }
+    counts.into_iter().max().unwrap_or(0)
+}
+
+impl Default for Minimax {
+    /// creates the algorithm for the embedded dictionary
+    fn default() -> Self {
+        Self::new(WordList::dictionary())
+    }
+}
+
+guess_by_suggesting!(Minimax);
+
+impl Suggester for Minimax {
+    fn rank(&mut self, history: &[Guess], k: usize) -> Vec<Suggestion> {
+        // prune the dictionary by only keeping words that could be a possible match
+        if let Some(last) = history.last() {
+            self.remaining.prune(last);
+        }
+
+        // the first guess is either a fixed opener, or it is scored like any other guess
+        if history.is_empty() {
+            if let Opener::Word(word) = &self.opener {
+                return vec![super::suggestion_for(word, self.remaining.iter().map(|w| (w.word, w.weight)), self.remaining.total())];
+            }
+        }
+
+        // the sum of the weights of all the remaining words in the dictionary
+        let remaining_weight = self.remaining.weight();
+        let remaining = &self.remaining;
+        let table = self.table;
+        let guesses = super::guess_pool(self.pool, self.words, remaining, history);
+        // the smaller the largest bucket the better, the bits only break ties
+        let top = scoring::top_by(&guesses, self.threads, k, |_, word| {
+            let bits = scoring::entropy(&scoring::buckets(table, word, remaining), remaining_weight);
+            scoring::round_bits(bits) / Self::MAX_BITS - largest_bucket(table, word, remaining) as f64
+        });
+        top.into_iter()
+            .map(|(i, goodness)| {
+                let word = guesses[i];
+                // only the goodness is kept while scoring, recompute the bits of the winners
+                let buckets = scoring::buckets(table, &word, remaining);
+                Suggestion {
+                    word: word.word.to_string(),
+                    bits: scoring::entropy(&buckets, remaining_weight),
+                    goodness,
+                    // probe words can't be the answer
+                    p_word: if i < remaining.len() { word.weight / remaining_weight } else { 0.0 },
+                    remaining: remaining.len(),
+                    remaining_mass: remaining_weight / self.remaining.total(),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+    use crate::{Correctness, Guess, Suggester, WordList};
+    use crate::algorithms::GuessPool;
+    use super::{largest_bucket, Minimax};
+
+    #[test]
+    fn ranks_by_the_largest_bucket() {
+        let words = WordList::dictionary();
+        let history = [Guess {
+            word: Cow::Borrowed("tares"),
+            mask: Correctness::try_from_str("wwwww").unwrap(),
+        }];
+        let remaining: Vec<_> = words.indexed().iter().filter(|w| history[0].matches(w.word)).copied().collect();
+        let ranked = Minimax::new(words).rank(&history, 5);
+        assert_eq!(ranked.len(), 5);
+
+        let largest: Vec<usize> = ranked
+            .iter()
+            .map(|s| {
+                let word = remaining.iter().find(|w| w.word == s.word).unwrap();
+                largest_bucket(None, word, &remaining)
+            })
+            .collect();
+        assert!(largest.windows(2).all(|l| l[0] <= l[1]), "{:?}", largest);
+        // no remaining word has a smaller largest bucket than the best one
+        assert!(remaining.iter().all(|w| largest_bucket(None, w, &remaining) >= largest[0]));
+        // words with the same largest bucket are ranked by their bits
+        for (s, l) in ranked.windows(2).zip(largest.windows(2)) {
+            if l[0] == l[1] {
+                assert!(s[0].bits >= s[1].bits - 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn probes_never_leave_a_larger_bucket() {
+        let words = WordList::dictionary();
+        let history = [Guess {
+            word: Cow::Borrowed("tares"),
+            mask: Correctness::try_from_str("wcwcc").unwrap(),
+        }];
+        let remaining = Minimax::new(words).rank(&history, 1).remove(0);
+        let probes = Minimax::new(words).with_pool(GuessPool::Dictionary).rank(&history, 1).remove(0);
+        assert!(probes.goodness >= remaining.goodness);
+    }
+}
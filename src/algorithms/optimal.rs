@@ -0,0 +1,326 @@
+//! An exact solver that finds the decision tree with the fewest total guesses for an answer
+//! list, where every answer is equally likely.
+//!
+//! Guessing `g` when any of the answers of the set `S` could be the answer costs one guess for
+//! every answer of `S`, and splits `S` into pattern buckets that are then solved on their own:
+//!
+//! `total(S) = min_g |S| + SUM_pat total(S_pat)`
+//!
+//! where the bucket of the all correct pattern costs nothing more. The search is a depth first
+//! branch-and-bound: no set of `n` answers can be solved in fewer than `2n - 1` guesses, one
+//! answer guessed right away and every other one in two, so the guesses of a set are tried from
+//! the lowest such bound, and a guess is abandoned as soon as its bound reaches the best total
+//! found so far. The total of every set solved exactly is memoized, since different guesses
+//! often leave the same set of answers.
+//!
+//! The search is exponential, it runs to completion quickly on a few hundred answers. For more,
+//! `with_breadth` only tries the most promising guesses of each set, and the total is then only
+//! an upper bound of the optimum.
+//!
+use std::collections::HashMap;
+use crate::{Error, PatternId, PatternTable, WordList};
+use super::scoring;
+use crate::word_list::Word;
+
+/// A decision tree that solves every answer of a set: guess `guess`, then follow the child of
+/// the pattern that was seen
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecisionTree {
+    /// the word to guess
+    pub guess: &'static str,
+    /// the number of answers the tree solves
+    pub answers: usize,
+    /// the sum of the number of guesses it takes to solve every answer of the tree
+    pub total: usize,
+    /// the tree that solves the answers of each pattern, other than the all correct pattern
+    pub children: Vec<(PatternId, DecisionTree)>,
+}
+
+impl DecisionTree {
+    /// returns the average number of guesses it takes to solve an answer of the tree
+    pub fn average(&self) -> f64 {
+        self.total as f64 / self.answers as f64
+    }
+
+    /// returns the number of guesses it takes the tree to solve `answer`, or `None` if the tree
+    /// does not solve it
+    pub fn guesses(&self, answer: &str) -> Option<usize> {
+        let pattern = PatternId::compute(answer, self.guess);
+        if pattern == PatternId::CORRECT {
+            return Some(1);
+        }
+        self.children
+            .iter()
+            .find(|(p, _)| *p == pattern)
+            .and_then(|(_, child)| child.guesses(answer))
+            .map(|g| g + 1)
+    }
+
+    /// returns the largest number of guesses it takes to solve an answer of the tree
+    pub fn depth(&self) -> usize {
+        1 + self.children.iter().map(|(_, c)| c.depth()).max().unwrap_or(0)
+    }
+}
+
+pub struct Optimal {
+    /// the dictionary, the words are taken from
+    words: &'static WordList,
+    /// pre-computed patterns of the dictionary, if they were loaded
+    table: Option<&'static PatternTable>,
+    /// the words that could be the answer
+    answers: Vec<Word>,
+    /// guess any dictionary word, not only the answers that are left
+    probes: bool,
+    /// the number of guesses tried for each set of answers, all of them if `None`
+    breadth: Option<usize>,
+    /// the total and the dictionary index of the best guess of every set of answers, keyed by the
+    /// sorted dictionary indices of the set, that was solved exactly
+    memo: HashMap<Vec<u32>, (usize, usize)>,
+}
+
+impl Optimal {
+    /// creates a solver that guesses the words of `words`, every word could be the answer. The
+    /// pattern table of the words is used if it was loaded, see `WordList::load_table`
+    pub fn new(words: &'static WordList) -> Self {
+        Self {
+            words,
+            table: words.table(),
+            answers: words.indexed().to_vec(),
+            probes: false,
+            breadth: None,
+            memo: HashMap::new(),
+        }
+    }
+
+    /// restricts the words that could be the answer to `answers`. Returns
+    /// `Error::InvalidWord` for an answer that is not in the dictionary
+    pub fn with_answers<'a>(mut self, answers: impl IntoIterator<Item=&'a str>) -> Result<Self, Error> {
+        let dictionary = self.words.indexed();
+        let mut candidates = answers
+            .into_iter()
+            .map(|word| {
+                self.words
+                    .index_of(word)
+                    .map(|index| dictionary[index])
+                    .ok_or_else(|| Error::InvalidWord(word.to_string()))
+            })
+            .collect::<Result<Vec<Word>, Error>>()?;
+        candidates.sort_unstable_by_key(|w| w.index);
+        candidates.dedup_by_key(|w| w.index);
+        self.answers = candidates;
+        self.memo.clear();
+        Ok(self)
+    }
+
+    /// lets any dictionary word be guessed when `probes` is `true`, by default only the answers
+    /// that are left are guessed. Probes can only lower the total, but make the search slower
+    pub fn with_probes(mut self, probes: bool) -> Self {
+        self.probes = probes;
+        self.memo.clear();
+        self
+    }
+
+    /// only tries the `breadth` guesses with the lowest bound for each set of answers, the total
+    /// is then an upper bound of the optimum. By default every guess is tried
+    pub fn with_breadth(mut self, breadth: usize) -> Self {
+        self.breadth = Some(breadth.max(1));
+        self.memo.clear();
+        self
+    }
+
+    /// searches for the decision tree that solves every answer with the fewest total guesses.
+    /// Returns `None` if there are no answers
+    pub fn solve(&mut self) -> Option<DecisionTree> {
+        if self.answers.is_empty() {
+            return None;
+        }
+        let answers = self.answers.clone();
+        self.total(&answers, usize::MAX);
+        Some(self.tree(&answers))
+    }
+
+    /// returns the fewest total guesses that solve every one of `answers`, sorted by index, or
+    /// `None` if it is not below `limit`
+    fn total(&mut self, answers: &[Word], limit: usize) -> Option<usize> {
+        let n = answers.len();
+        if n <= 2 {
+            // guess one of them, then the other if it was not the answer
+            let total = 2 * n - 1;
+            return (total < limit).then_some(total);
+        }
+        let key = key(answers);
+        if let Some(&(total, _)) = self.memo.get(&key) {
+            return (total < limit).then_some(total);
+        }
+
+        let mut best = limit;
+        let mut best_guess = None;
+        for (bound, guess) in self.candidates(answers) {
+            if bound >= best {
+                // the candidates are sorted by their bound, no other guess can do better
+                break;
+            }
+            let buckets = self.split(&guess, answers);
+            // the bound of the guess, with the total of every bucket solved so far
+            let mut total = bound;
+            for bucket in &buckets {
+                let bucket_bound = 2 * bucket.len() - 1;
+                match self.total(bucket, best - (total - bucket_bound)) {
+                    Some(t) => total += t - bucket_bound,
+                    None => {
+                        total = best;
+                        break;
+                    }
+                }
+            }
+            if total < best {
+                best = total;
+                best_guess = Some(guess.index);
+            }
+        }
+
+        let guess = best_guess?;
+        self.memo.insert(key, (best, guess));
+        Some(best)
+    }
+
+    /// returns the bound and the word of the guesses worth trying for `answers`, lowest bound
+    /// first. Guesses that can not tell any of the answers apart are left out
+    fn candidates(&self, answers: &[Word]) -> Vec<(usize, Word)> {
+        let guesses = if self.probes { self.words.indexed() } else { answers };
+        let n = answers.len();
+        // (bound, whether the guess can't be the answer, guess)
+        let mut candidates: Vec<(usize, bool, Word)> = guesses
+            .iter()
+            .filter_map(|guess| {
+                let mut counts = [0usize; PatternId::COUNT];
+                for answer in answers {
+                    counts[scoring::pattern(self.table, guess, answer).index()] += 1;
+                }
+                let probe = counts[PatternId::CORRECT.index()] == 0;
+                if probe && counts.contains(&n) {
+                    return None;
+                }
+                counts[PatternId::CORRECT.index()] = 0;
+                let bound = n + counts.iter().filter(|&&c| c > 0).map(|&c| 2 * c - 1).sum::<usize>();
+                Some((bound, probe, *guess))
+            })
+            .collect();
+        // ties go to guesses that could be the answer, then to dictionary order
+        candidates.sort_unstable_by_key(|&(bound, probe, guess)| (bound, probe, guess.index));
+        if let Some(breadth) = self.breadth {
+            candidates.truncate(breadth);
+        }
+        candidates.into_iter().map(|(bound, _, guess)| (bound, guess)).collect()
+    }
+
+    /// splits `answers` into the buckets of the patterns seen when guessing `guess`, leaving
+    /// out the answer `guess` itself. The buckets keep the order of `answers`
+    fn split(&self, guess: &Word, answers: &[Word]) -> Vec<Vec<Word>> {
+        let mut buckets: Vec<Vec<Word>> = vec![Vec::new(); PatternId::COUNT];
+        for answer in answers {
+            buckets[scoring::pattern(self.table, guess, answer).index()].push(*answer);
+        }
+        buckets[PatternId::CORRECT.index()].clear();
+        buckets.retain(|b| !b.is_empty());
+        buckets
+    }
+
+    /// builds the decision tree of `answers` from the memoized best guesses
+    fn tree(&mut self, answers: &[Word]) -> DecisionTree {
+        let guess = if answers.len() <= 2 {
+            answers[0]
+        } else {
+            // every set of the best tree was solved exactly, this only looks up its memo
+            self.total(answers, usize::MAX);
+            self.words.indexed()[self.memo[&key(answers)].1]
+        };
+        let children: Vec<(PatternId, DecisionTree)> = self
+            .split(&guess, answers)
+            .iter()
+            .map(|bucket| (scoring::pattern(self.table, &guess, &bucket[0]), self.tree(bucket)))
+            .collect();
+        DecisionTree {
+            guess: guess.word,
+            answers: answers.len(),
+            total: answers.len() + children.iter().map(|(_, c)| c.total).sum::<usize>(),
+            children,
+        }
+    }
+}
+
+/// returns the memo key of `answers`, their dictionary indices
+fn key(answers: &[Word]) -> Vec<u32> {
+    answers.iter().map(|w| w.index as u32).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Error, WordList};
+    use super::Optimal;
+
+    /// returns the fewest total guesses of `answers` by trying every guess at every level,
+    /// without any bounds or memoization
+    fn brute_force(answers: &[&'static str]) -> usize {
+        if answers.len() <= 1 {
+            return answers.len();
+        }
+        answers
+            .iter()
+            .map(|&guess| {
+                let mut buckets = std::collections::BTreeMap::new();
+                for &answer in answers.iter().filter(|&&a| a != guess) {
+                    buckets.entry(crate::PatternId::compute(answer, guess)).or_insert_with(Vec::new).push(answer);
+                }
+                answers.len() + buckets.values().map(|b| brute_force(b)).sum::<usize>()
+            })
+            .min()
+            .unwrap()
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        let answers: Vec<&'static str> = WordList::answers().words().take(12).collect();
+        let mut optimal = Optimal::new(WordList::dictionary()).with_answers(answers.iter().copied()).unwrap();
+        let tree = optimal.solve().unwrap();
+        assert_eq!(tree.total, brute_force(&answers));
+        assert_eq!(tree.answers, answers.len());
+
+        // the tree solves every answer, in the number of guesses it claims
+        let total: usize = answers.iter().map(|a| tree.guesses(a).unwrap()).sum();
+        assert_eq!(total, tree.total);
+        assert!(tree.depth() <= answers.len());
+    }
+
+    #[test]
+    fn probes_and_breadth_bound_the_optimum() {
+        let answers: Vec<&'static str> = WordList::answers().words().take(40).collect();
+        let dictionary = WordList::dictionary();
+        let exact = Optimal::new(dictionary).with_answers(answers.iter().copied()).unwrap().solve().unwrap();
+        let narrow = Optimal::new(dictionary)
+            .with_answers(answers.iter().copied())
+            .unwrap()
+            .with_breadth(1)
+            .solve()
+            .unwrap();
+        assert!(narrow.total >= exact.total);
+
+        // words that can't be the answer never make the optimum worse
+        let probes = Optimal::new(dictionary)
+            .with_answers(answers[..10].iter().copied())
+            .unwrap()
+            .with_probes(true)
+            .solve()
+            .unwrap();
+        let remaining = Optimal::new(dictionary).with_answers(answers[..10].iter().copied()).unwrap().solve().unwrap();
+        assert!(probes.total <= remaining.total);
+        assert!(answers[..10].iter().all(|a| probes.guesses(a).is_some()));
+    }
+
+    #[test]
+    fn answers_must_be_in_the_dictionary() {
+        let result = Optimal::new(WordList::dictionary()).with_answers(["cigar", "zzzzz"]);
+        assert_eq!(result.err(), Some(Error::InvalidWord("zzzzz".to_string())));
+        assert!(Optimal::new(WordList::dictionary()).with_answers([]).unwrap().solve().is_none());
+    }
+}
@@ -4,11 +4,12 @@
 use std::borrow::Cow;
 use std::collections::{BTreeMap};
 use std::sync::OnceLock;
-use crate::{Guesser, Guess, DICTIONARY, Correctness};
+use super::Opener;
+use crate::{Error, Guesser, Guess, WordList, Correctness, Suggester, Suggestion};
 
-// holds the initial list of (word, count) from the dictionary, loaded only once
-static INITIAL: OnceLock<Vec<(&'static str, usize)>> = OnceLock::new();
-static MATCH: OnceLock<BTreeMap<(&'static str, &'static str, [Correctness; 5]), bool>> = OnceLock::new();
+/// maps a `(word, word, mask)` key to the result of a `Guess::matches` check
+type MatchMap = BTreeMap<(&'static str, &'static str, [Correctness; 5]), bool>;
+static MATCH: OnceLock<MatchMap> = OnceLock::new();
 
 pub struct PreCalc {
     /// a map containing all possible words that could be a possible solution
@@ -16,31 +17,37 @@ pub struct PreCalc {
     /// that word appeared in books
     // Cow is used because we are either going to be borrowing a Dictionary or we are going to
     // own a dictionary once we start pruning words
-    remaining: Cow<'static, Vec<(&'static str, usize)>>,
+    remaining: Cow<'static, [(&'static str, usize)]>,
+    /// the dictionary, the words are taken from
+    words: &'static WordList,
+    /// the first word guessed
+    opener: Opener,
 }
 
 impl PreCalc {
 
     /// Creates a new Once algorithm for solving wordle
-    pub fn new() -> Self {
+    pub fn new(words: &'static WordList) -> Self {
         Self {
-            remaining: Cow::Borrowed(INITIAL.get_or_init(|| {
-                // sort initial words in DESCSENDING order
-                let mut words = Vec::from_iter(
-                    DICTIONARY
-                        .lines()
-                        .map(|line| {
-                            let (word, count) = line
-                                .split_once(' ')
-                                .expect("every line is a word + space + occurrence_count");
-                            let count: usize = count.parse().expect("every count is a number");
-                            (word, count)
-                        }));
-                words.sort_unstable_by_key(|&(_, c)| std::cmp::Reverse(c));
-                words
-            })),
+            // sorted from the most to the least common word
+            remaining: Cow::Borrowed(words.by_count()),
+            words,
+            opener: Opener::default(),
         }
     }
+
+    /// sets the first word to guess, the default is "tares"
+    pub fn with_opener(mut self, opener: Opener) -> Self {
+        self.opener = opener;
+        self
+    }
+}
+
+impl Default for PreCalc {
+    /// creates the algorithm for the embedded dictionary
+    fn default() -> Self {
+        Self::new(WordList::dictionary())
+    }
 }
 
 /// Holds the details of a potential best guess
@@ -50,11 +57,28 @@ struct Candidate {
     word: &'static str,
     /// the candidates 'goodness' score, or entropy 'bits'. Higher is better
     goodness: f64,
+    /// the expected amount of information, in bits, gained by guessing the word
+    bits: f64,
+    /// the probability that the word is the answer
+    p_word: f64,
 }
 
 impl Guesser for PreCalc {
-
     fn guess(&mut self, history: &[Guess]) -> String {
+        self.suggest(history).word
+    }
+
+    fn try_guess(&mut self, history: &[Guess]) -> Result<String, Error> {
+        self.try_suggest(history).map(|s| s.word)
+    }
+
+    fn try_guess_remaining(&mut self, history: &[Guess]) -> Result<(String, Option<usize>), Error> {
+        self.try_suggest(history).map(|s| (s.word, Some(s.remaining)))
+    }
+}
+
+impl Suggester for PreCalc {
+    fn rank(&mut self, history: &[Guess], k: usize) -> Vec<Suggestion> {
 
         // prune the dictionary by only keeping words that could be a possible match
         if let Some(last) = history.last() {
@@ -74,19 +98,21 @@ impl Guesser for PreCalc {
 
         }
 
-        // hardcode the first guess to "tares"
+        // the first guess is either a fixed opener, or it is scored like any other guess
         if history.is_empty() {
-            return "tares".to_string();
+            if let Opener::Word(word) = &self.opener {
+                return vec![super::suggestion_for(word, self.remaining.iter().map(|&(w, c)| (w, c as f64)), self.words.total() as f64)];
+            }
         }
 
         // the sum of the counts of all the remaining words in the dictionary
         let remaining_count: usize = self.remaining
             .iter()
             .map(|&(_, c)| c).sum();
-        // the best word
-        let mut best: Option<Candidate> = None;
+        // the best k words, best first
+        let mut top: Vec<Candidate> = Vec::with_capacity(k + 1);
 
-        for &(word, _) in &*self.remaining {
+        for &(word, count) in &*self.remaining {
             let mut sum = 0.0;
 
             // todo don't consider correctness patterns that had no candidates in the previous
@@ -105,8 +131,8 @@ impl Guesser for PreCalc {
                         // considering a "world" where we did guess "word" and got "pattern" as the
                         // correctness. Now compute what _then_ is left
 
-                        let initial_words = &INITIAL.get().expect("INITIAL vec of dictionary words is loaded")[..1024];
-                        dbg!(&initial_words[..10]);
+                        let by_count = self.words.by_count();
+                        let initial_words = &by_count[..by_count.len().min(1024)];
                         let mut out = BTreeMap::new();
 
                         for &(word1, _) in initial_words {
@@ -148,16 +174,22 @@ impl Guesser for PreCalc {
             }
             // negate the sum to get the final goodness amount, a.k.a the entropy "bits"
             // TODO weight this by prob_word
-            let goodness = -sum;
+            let bits = -sum;
+            let goodness = bits;
+            // the probability of the current word, used to report the suggestion
+            let p_word = count as f64 / remaining_count as f64;
 
-            if let Some(c) = best {
-                if goodness > c.goodness {
-                    best = Some(Candidate { word, goodness })
-                }
-            } else {
-                best = Some(Candidate { word, goodness })
-            }
+            super::rank_insert(&mut top, k, Candidate { word, goodness, bits, p_word }, |c| c.goodness);
         }
-        best.unwrap().word.to_string()
+        top.into_iter()
+            .map(|c| Suggestion {
+                word: c.word.to_string(),
+                bits: c.bits,
+                goodness: c.goodness,
+                p_word: c.p_word,
+                remaining: self.remaining.len(),
+                remaining_mass: remaining_count as f64 / self.words.total() as f64,
+            })
+            .collect()
     }
 }
\ No newline at end of file
@@ -3,63 +3,179 @@
 //!
 use std::borrow::Cow;
 use std::sync::OnceLock;
-use crate::{Guesser, Guess, DICTIONARY, Correctness};
+use super::{GuessPool, Opener, Remaining};
+use crate::{Guess, WordList, Correctness, PatternId, PatternTable, Prior, Suggester, Suggestion};
+use super::scoring;
+use crate::word_list::Word;
 
-static INITIAL: OnceLock<Vec<(&'static str, usize)>> = OnceLock::new();
 static PATTERNS: OnceLock<Vec<[Correctness; 5]>> = OnceLock::new();
 
+/// The ways `Prune` can compute the goodness score of a candidate word. Both give the remaining
+/// words the same scores, `p_word * bits`, and suggest the same words. A pattern that matches no
+/// remaining word for one candidate is pruned for every later candidate and guess, so later
+/// candidates may be scored with fewer bits than `Weight` gives them. Only `Buckets` scores in
+/// parallel and guesses probe words, see `Prune::with_pool`, which are scored by their exact bits
+/// without pruning any pattern
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Scoring {
+    /// iterate every correctness pattern that has not been pruned yet, and for each one re-scan
+    /// all remaining words, O(243 * n^2)
+    Patterns,
+    /// compute the pattern of every (word, remaining word) pair once and accumulate the weights
+    /// into 243 buckets, O(n^2). Uses the dictionary's `PatternTable` if it was loaded
+    Buckets,
+}
+
 pub struct Prune {
-    /// a `Vec<(word, count)>` containing all possible words (and their occurrence count) that
-    /// could be a possible solution.
-    // Cow is used because we are either going to be borrowing a Dictionary or we are going to
-    // own a dictionary once we start pruning words
-    remaining: Cow<'static, Vec<(&'static str, usize)>>,
-    /// holds all possible wordle correctness patterns, 3^5 elements
-    patterns: Cow<'static, Vec<[Correctness; 5]>>,
+    /// all possible words (and their prior weight) that could be a possible solution, the whole
+    /// dictionary or only the answers
+    remaining: Remaining,
+    /// the wordle correctness patterns, by `PatternId`, that have not been pruned yet
+    live: [bool; PatternId::COUNT],
+    /// how candidate words are scored
+    scoring: Scoring,
+    /// pre-computed patterns of the dictionary, if they were loaded
+    table: Option<&'static PatternTable>,
+    /// number of threads used to score candidate words
+    threads: usize,
+    /// the words that may be guessed
+    pool: GuessPool,
+    /// the dictionary, the words are taken from
+    words: &'static WordList,
+    /// the first word guessed
+    opener: Opener,
 }
 
 impl Prune {
-    /// creates a new Prune algo, loads the word dictionary if not already loaded
-    pub fn new() -> Self {
+    /// creates a new Prune algo that guesses the words of `words`. The pattern table of the
+    /// words is used if it was loaded, see `WordList::load_table`
+    pub fn new(words: &'static WordList) -> Self {
         Self {
-            remaining: Cow::Borrowed(INITIAL.get_or_init(|| {
-                Vec::from_iter(
-                    DICTIONARY
-                        .lines()
-                        .map(|line| {
-                            let (word, count) = line
-                                .split_once(' ')
-                                .expect("every line is a word + space + occurrence_count");
-                            let count: usize = count.parse().expect("every count is a number");
-                            (word, count)
-                        }))
-            })),
-            patterns: Cow::Borrowed(PATTERNS.get_or_init(|| Vec::from_iter(Correctness::patterns()))),
+            remaining: Remaining::new(words),
+            live: [true; PatternId::COUNT],
+            scoring: Scoring::Buckets,
+            table: words.table(),
+            threads: 1,
+            pool: GuessPool::Remaining,
+            words,
+            opener: Opener::default(),
         }
     }
 
-    /// prune the list of remaining words by only keeping words that could be a possible match
-    /// with the `last_guess`
-    fn prune_remaining(&mut self, last_guess: &Guess) {
-        if matches!(self.remaining, Cow::Owned(_)) {
-            // if the remaining Vec is already owned, mutate it to retain the matching words
-            self.remaining
-                .to_mut()
-                .retain(|(word, _)| last_guess.matches(word));
-        } else {
-            // else, create a new owned Vec, but first, filter the matching words
-            self.remaining = Cow::Owned(self.remaining
-                .iter()
-                .filter(|(word, _)| last_guess.matches(word))
-                .copied()
-                .collect());
+    /// sets the first word to guess, the default is "tares"
+    pub fn with_opener(mut self, opener: Opener) -> Self {
+        self.opener = opener;
+        self
+    }
+
+    /// sets how candidate words are scored, the default is `Scoring::Buckets`
+    pub fn with_scoring(mut self, scoring: Scoring) -> Self {
+        self.scoring = scoring;
+        self
+    }
+
+    /// sets the number of threads used to score candidate words, the default is one.
+    /// The remaining words are split evenly across the threads. Only `Scoring::Buckets` scores
+    /// in parallel, `Scoring::Patterns` prunes patterns as it goes and always uses one thread
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// sets the words that may be guessed, the default is `GuessPool::Remaining`.
+    /// `Scoring::Patterns` only ever guesses remaining words
+    pub fn with_pool(mut self, pool: GuessPool) -> Self {
+        self.pool = pool;
+        self
+    }
+
+    /// restricts the words that could be the answer to the words of `answers`, keeping the
+    /// weights the prior gives them in the dictionary, see `with_prior`. By default every
+    /// dictionary word could be the answer. Any dictionary word can still be guessed as a probe,
+    /// see `with_pool`. Answers that are not in the dictionary are ignored
+    pub fn with_answers(mut self, answers: &'static WordList) -> Self {
+        self.remaining.restrict(answers);
+        self
+    }
+
+    /// sets how likely each word is to be the answer, the default is `Prior::Count`. The weights
+    /// are always computed over the whole dictionary, even if the answers were restricted
+    pub fn with_prior(mut self, prior: Prior) -> Self {
+        self.remaining.set_prior(prior);
+        self
+    }
+
+    /// returns the words that could still be the answer, along with their prior weight
+    pub(crate) fn remaining(&self) -> &[Word] {
+        &self.remaining
+    }
+
+    /// computes the negated entropy sum of `word` by iterating the correctness patterns that
+    /// have not been pruned yet. Any pattern that can no longer match a remaining word is pruned
+    fn pattern_sum(&mut self, word: &'static str, remaining_weight: f64) -> f64 {
+        // sum of all prob_of_a_pattern * prob_of_a_pattern.log2
+        let mut sum = 0.0;
+        let remaining = &self.remaining;
+        let patterns = PATTERNS.get_or_init(|| Vec::from_iter(Correctness::patterns()));
+
+        for (pattern, live) in patterns.iter().zip(self.live.iter_mut()) {
+            if !*live {
+                continue;
+            }
+            // sum of the weight(s) of all words that match the pattern
+            let mut in_pattern_total = 0.0;
+
+            // given a particular candidate word, if we guess this word, what
+            // are the probabilities of getting each pattern. We sum together all those
+            // probabilities and use that to determine the entropy information amount from
+            // guessing that word
+            for candidate in &**remaining {
+                // considering a "world" where we did guess "word" and got "pattern" as the
+                // correctness. Now compute what _then_ is left
+                let g = Guess {
+                    word: Cow::Borrowed(word),
+                    mask: *pattern,
+                };
+                if g.matches(candidate.word) {
+                    in_pattern_total += candidate.weight;
+                }
+            }
+            if in_pattern_total == 0.0 {
+                // no candidate words matched the pattern, prune it out as it can not match
+                // anymore
+                *live = false;
+                continue;
+            }
+            let prob_of_this_pattern = in_pattern_total / remaining_weight;
+            sum += prob_of_this_pattern * prob_of_this_pattern.log2();
         }
+        sum
+    }
+
+    /// computes the negated entropy sum of a word from its pattern `buckets`, like `pattern_sum`
+    /// does, only summing the patterns that have not been pruned yet, and pruning the ones that
+    /// no remaining word falls into
+    fn bucket_sum(&mut self, buckets: &[f64; PatternId::COUNT], remaining_weight: f64) -> f64 {
+        let mut sum = 0.0;
+        for (&in_pattern_total, live) in buckets.iter().zip(self.live.iter_mut()) {
+            if !*live {
+                continue;
+            }
+            if in_pattern_total == 0.0 {
+                *live = false;
+                continue;
+            }
+            let prob_of_this_pattern = in_pattern_total / remaining_weight;
+            sum += prob_of_this_pattern * prob_of_this_pattern.log2();
+        }
+        sum
     }
 }
 
 impl Default for Prune {
+    /// creates the algorithm for the embedded dictionary
     fn default() -> Self {
-        Self::new()
+        Self::new(WordList::dictionary())
     }
 }
 
@@ -70,88 +186,220 @@ struct Candidate {
     word: &'static str,
     /// the candidates 'goodness' score, or entropy 'bits'. Higher is better
     goodness: f64,
+    /// the expected amount of information, in bits, gained by guessing the word
+    bits: f64,
+    /// the probability that the word is the answer
+    p_word: f64,
 }
 
-impl Guesser for Prune {
-    fn guess(&mut self, history: &[Guess]) -> String {
+guess_by_suggesting!(Prune);
+
+impl Suggester for Prune {
+    fn rank(&mut self, history: &[Guess], k: usize) -> Vec<Suggestion> {
         if let Some(last) = history.last() {
-            self.prune_remaining(last);
+            self.remaining.prune(last);
         }
 
-        // hardcode the first guess to "tares"
+        // the first guess is either a fixed opener, or it is scored like any other guess
         if history.is_empty() {
-            self.patterns = Cow::Borrowed(PATTERNS.get().unwrap());
-            return "tares".to_string();
+            self.live = [true; PatternId::COUNT];
+            if let Opener::Word(word) = &self.opener {
+                let s = super::suggestion_for(word, self.remaining.iter().map(|w| (w.word, w.weight)), self.remaining.total());
+                return vec![Suggestion { goodness: s.p_word * s.bits, ..s }];
+            }
         } else {
             // there should be patterns left if we are still guessing
-            assert!(!self.patterns.is_empty());
+            assert!(self.live.contains(&true));
         }
 
-        // the sum of the counts of all the remaining words in the dictionary
-        let remaining_word_count: usize = self.remaining
-            .iter()
-            .map(|&(_, c)| c).sum();
-        // the best candidate so far
-        let mut best: Option<Candidate> = None;
-
-        for &(word, count) in &*self.remaining {
-            // sum of all prob_of_a_pattern * prob_of_a_pattern.log2
-            let mut sum = 0.0;
-
-            // checks if the given pattern matches any candidate words
-            // returns true if the pattern matches, false if it did not
-            let check_pattern = |pattern: &[Correctness; 5]| {
-                // sum of the count(s) of all words that match the pattern
-                let mut in_pattern_total: usize = 0;
-
-                // given a particular candidate word, if we guess this word, what
-                // are the probabilities of getting each pattern. We sum together all those
-                // probabilities and use that to determine the entropy information amount from
-                // guessing that word
-                for &(candidate, count) in &*self.remaining {
-                    // considering a "world" where we did guess "word" and got "pattern" as the
-                    // correctness. Now compute what _then_ is left
-                    let g = Guess {
-                        word: Cow::Borrowed(word),
-                        mask: *pattern,
-                    };
-                    if g.matches(candidate) {
-                        in_pattern_total += count;
-                    }
+        // the sum of the weights of all the remaining words in the dictionary
+        let remaining_weight = self.remaining.weight();
+        // the best k candidates, best first
+        let top: Vec<Candidate> = match self.scoring {
+            Scoring::Patterns => {
+                let mut top = Vec::with_capacity(k + 1);
+                for i in 0..self.remaining.len() {
+                    let word = self.remaining[i];
+                    let sum = self.pattern_sum(word.word, remaining_weight);
+                    // compute the probability of the current `word` using its prior `weight`
+                    let p_word = word.weight / remaining_weight;
+                    // the goodnees score of `word` a.k.a its entropy "bits"
+                    let bits = -sum;
+                    let goodness = p_word * bits;
+
+                    let candidate = Candidate { word: word.word, goodness, bits, p_word };
+                    super::rank_insert(&mut top, k, candidate, |c| c.goodness);
                 }
-                if in_pattern_total == 0 {
-                    // no candidate words matched the pattern
-                    return false;
+                top
+            }
+            Scoring::Buckets if self.pool == GuessPool::Remaining => {
+                let remaining = &self.remaining;
+                let table = self.table;
+                // the buckets are computed in parallel, but the patterns are pruned in the order
+                // of the words, which keeps the scores the same as `Scoring::Patterns`
+                let all_buckets = scoring::map(remaining, self.threads, |word| scoring::buckets(table, word, remaining));
+                let mut top = Vec::with_capacity(k + 1);
+                for (i, buckets) in all_buckets.iter().enumerate() {
+                    let word = self.remaining[i];
+                    let bits = -self.bucket_sum(buckets, remaining_weight);
+                    let p_word = word.weight / remaining_weight;
+                    let candidate = Candidate { word: word.word, goodness: p_word * bits, bits, p_word };
+                    super::rank_insert(&mut top, k, candidate, |c| c.goodness);
                 }
-                let prob_of_this_pattern = in_pattern_total as f64 / remaining_word_count as f64;
-                sum += prob_of_this_pattern * prob_of_this_pattern.log2();
-                true
-            };
-
-            // retain any patterns that still can possibly match candidate words, prune out
-            // any that can not match anymore
-            if matches!(self.patterns, Cow::Owned(_)) {
-                self.patterns.to_mut().retain(check_pattern);
-            } else {
-                self.patterns = Cow::Owned(self.patterns
-                    .iter()
-                    .copied()
-                    .filter(check_pattern)
-                    .collect());
+                top
+            }
+            Scoring::Buckets => {
+                let remaining = &self.remaining;
+                let table = self.table;
+                // the words that may be guessed, the remaining words always come first so that
+                // they win ties against probe words
+                let guesses = super::guess_pool(self.pool, self.words, remaining, history);
+                // the probability of the guess at index `i` being the answer, probe words can't be
+                let p_word = |i: usize, word: &Word| {
+                    if i < remaining.len() {
+                        word.weight / remaining_weight
+                    } else {
+                        0.0
+                    }
+                };
+                // a probe word never wins outright, so only its information counts
+                scoring::top_by(&guesses, self.threads, k, |_, word| {
+                    let buckets = scoring::buckets(table, word, remaining);
+                    scoring::round_bits(scoring::entropy(&buckets, remaining_weight))
+                })
+                    .into_iter()
+                    .map(|(i, goodness)| {
+                        let word = guesses[i];
+                        // only the goodness is kept while scoring, recompute the bits of the winners
+                        let buckets = scoring::buckets(table, &word, remaining);
+                        Candidate {
+                            word: word.word,
+                            goodness,
+                            bits: scoring::entropy(&buckets, remaining_weight),
+                            p_word: p_word(i, &word),
+                        }
+                    })
+                    .collect()
             }
-            // compute the probability of the current `word` using its occurrence `count`
-            let p_word = count as f64 / remaining_word_count as f64;
-            // the goodnees score of `word` a.k.a its entropy "bits"
-            let goodness = p_word * -sum;
-
-            if let Some(c) = best {
-                if goodness > c.goodness {
-                    best = Some(Candidate { word, goodness })
+        };
+        top.into_iter()
+            .map(|c| Suggestion {
+                word: c.word.to_string(),
+                bits: c.bits,
+                goodness: c.goodness,
+                p_word: c.p_word,
+                remaining: self.remaining.len(),
+                remaining_mass: remaining_weight / self.remaining.total(),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+    use crate::{Correctness, Guess, Suggester, Suggestion, WordList};
+    use crate::algorithms::{GuessPool, Weight};
+    use super::{Prune, Scoring};
+
+    /// histories that leave few enough words for the slower scorings
+    const HISTORIES: [&[(&str, &str)]; 2] = [
+        &[("tares", "wcwcw")],
+        &[("tares", "cwwmw")],
+    ];
+
+    /// ranks the top `k` words of `guesser` after each guess of `history`
+    fn rank(mut guesser: impl Suggester, history: &[(&str, &str)], k: usize) -> Vec<Suggestion> {
+        let history: Vec<Guess> = history
+            .iter()
+            .map(|&(word, mask)| Guess { word: Cow::Borrowed(word), mask: Correctness::try_from_str(mask).unwrap() })
+            .collect();
+        for i in 0..history.len() {
+            guesser.rank(&history[..i], 1);
+        }
+        guesser.rank(&history, k)
+    }
+
+    /// asserts that both rankings have the same goodness at each rank, and the same words apart
+    /// from the words tied with the last one, which may be ranked in another order
+    fn assert_same_top(a: &[Suggestion], b: &[Suggestion]) {
+        assert_eq!(a.len(), b.len());
+        for (x, y) in a.iter().zip(b) {
+            assert!((x.goodness - y.goodness).abs() < 1e-9, "{} {} != {} {}", x.word, x.goodness, y.word, y.goodness);
+            assert!((x.bits - y.bits).abs() < 1e-9);
+        }
+        let last = a[a.len() - 1].goodness;
+        for x in a.iter().filter(|x| x.goodness > last + 1e-9) {
+            assert!(b.iter().any(|y| y.word == x.word), "{} is not ranked", x.word);
+        }
+    }
+
+    /// plays every one of the `answers` with both scorings, asserting that they rank the same
+    /// words with the same scores before every guess
+    fn assert_same_games<'a>(answers: impl Iterator<Item=&'a str>) {
+        let words = WordList::dictionary();
+        for answer in answers {
+            let mut patterns = Prune::new(words).with_scoring(Scoring::Patterns);
+            let mut buckets = Prune::new(words).with_scoring(Scoring::Buckets).with_threads(4);
+            let mut history = Vec::new();
+            loop {
+                let expected = patterns.rank(&history, 5);
+                assert_eq!(buckets.rank(&history, 5), expected, "{} after {} guesses", answer, history.len());
+                let guess = expected[0].word.clone();
+                if guess == answer {
+                    break;
                 }
-            } else {
-                best = Some(Candidate { word, goodness })
+                let mask = Correctness::compute(answer, &guess);
+                history.push(Guess { word: Cow::Owned(guess), mask });
             }
         }
-        best.expect("there should be words left that match the correctness pattern, perhaps a typo in the pattern").word.to_string()
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn buckets_suggest_like_patterns() {
+        assert_same_games(WordList::answers().words().take(20));
+    }
+
+    #[test]
+    #[ignore = "plays every answer, run with --release --ignored"]
+    fn buckets_suggest_like_patterns_on_every_answer() {
+        assert_same_games(WordList::answers().words());
+    }
+
+    #[test]
+    fn pruned_patterns_never_score_more_bits_than_weight() {
+        let words = WordList::dictionary();
+        for history in HISTORIES {
+            // rank every remaining word
+            let buckets = rank(Prune::new(words), history, 1000);
+            let weight = rank(Weight::new(words), history, 1000);
+            assert_eq!(buckets.len(), weight.len());
+            for b in &buckets {
+                let w = weight.iter().find(|w| w.word == b.word).unwrap();
+                assert!(b.bits <= w.bits + 1e-9, "{}: {} > {}", b.word, b.bits, w.bits);
+                assert_eq!(b.p_word, w.p_word);
+            }
+            // no pattern was pruned yet when the first remaining word was scored
+            let first = words.indexed().iter().find(|w| buckets.iter().any(|b| b.word == w.word)).unwrap();
+            let b = buckets.iter().find(|b| b.word == first.word).unwrap();
+            let w = weight.iter().find(|w| w.word == first.word).unwrap();
+            assert!((b.bits - w.bits).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn buckets_rank_like_threads() {
+        let words = WordList::dictionary();
+        for history in HISTORIES {
+            let buckets = rank(Prune::new(words), history, 5);
+            let threads = rank(Prune::new(words).with_threads(4), history, 5);
+            assert_eq!(buckets, threads);
+        }
+        // probe words are scored in parallel too
+        for history in HISTORIES {
+            let buckets = rank(Prune::new(words).with_pool(GuessPool::Dictionary), history, 5);
+            let threads = rank(Prune::new(words).with_pool(GuessPool::Dictionary).with_threads(4), history, 5);
+            assert_same_top(&buckets, &threads);
+        }
+    }
+}
@@ -0,0 +1,208 @@
+//! Bucket based entropy scoring shared by the guessing algorithms.
+//!
+//! Instead of iterating every correctness pattern and re-scanning all remaining words for each
+//! one, the pattern of every (guess, remaining word) pair is computed once and its count is
+//! accumulated into one of 243 buckets, one per `PatternId`. The entropy of a guess can then be
+//! read from the non-empty buckets, making the scoring of `n` words against `n` words O(n^2).
+//!
+use crate::{PatternId, PatternTable};
+use crate::word_list::Word;
+
+/// returns the pattern seen when guessing `guess` and the answer is `answer`, looking it up in
+/// `table` if one is available
+#[inline]
+pub(crate) fn pattern(table: Option<&PatternTable>, guess: &Word, answer: &Word) -> PatternId {
+    match table {
+        Some(table) => table.get(guess.index, answer.index),
+        None => PatternId::compute(answer.word, guess.word),
+    }
+}
+
+/// accumulates the total weight of the `answers` that fall into each pattern bucket if `guess`
+/// were guessed
+pub(crate) fn buckets(
+    table: Option<&PatternTable>,
+    guess: &Word,
+    answers: &[Word],
+) -> [f64; PatternId::COUNT] {
+    let mut buckets = [0.0; PatternId::COUNT];
+    for answer in answers {
+        buckets[pattern(table, guess, answer).index()] += answer.weight;
+    }
+    buckets
+}
+
+/// computes the entropy "bits", `- SUM_i prob_i * log_2(prob_i)`, of the pattern distribution in
+/// `buckets`, where `total` is the sum of all bucket weights
+pub(crate) fn entropy(buckets: &[f64; PatternId::COUNT], total: f64) -> f64 {
+    let sum: f64 = buckets
+        .iter()
+        .filter(|&&in_pattern_total| in_pattern_total > 0.0)
+        .map(|&in_pattern_total| {
+            let prob_of_this_pattern = in_pattern_total / total;
+            prob_of_this_pattern * prob_of_this_pattern.log2()
+        })
+        .sum();
+    -sum
+}
+
+/// computes the entropy "bits" of the answer itself when any of the `answers` could be the
+/// answer with a probability proportional to its weight, the uncertainty that is left to resolve
+pub(crate) fn uncertainty(answers: &[Word]) -> f64 {
+    let total: f64 = answers.iter().map(|w| w.weight).sum();
+    let sum: f64 = answers
+        .iter()
+        .filter(|w| w.weight > 0.0)
+        .map(|w| {
+            let p = w.weight / total;
+            p * p.log2()
+        })
+        .sum();
+    -sum
+}
+
+/// rounds entropy `bits` so that guesses with the same bucket sizes, but in different patterns,
+/// compare as equal. Summing the same probabilities in a different order can change the last
+/// bits of an f64
+pub(crate) fn round_bits(bits: f64) -> f64 {
+    (bits * 1e9).round() / 1e9
+}
+
+/// computes `f` of every word of `words`, in the order of the words. The words are split into
+/// `threads` contiguous chunks that are computed on their own worker threads
+pub(crate) fn map<T, F>(words: &[Word], threads: usize, f: F) -> Vec<T>
+    where T: Send, F: Fn(&Word) -> T + Sync
+{
+    if threads <= 1 || words.len() < 2 {
+        return words.iter().map(f).collect();
+    }
+
+    let chunk_len = words.len().div_ceil(threads);
+    let f = &f;
+    std::thread::scope(|s| {
+        let handles: Vec<_> = words
+            .chunks(chunk_len)
+            .map(|chunk| s.spawn(move || chunk.iter().map(f).collect::<Vec<T>>()))
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|h| h.join().expect("scoring thread panicked"))
+            .collect()
+    })
+}
+
+/// scores every word of `words` with `score`, which is given the index of the word and the word,
+/// and returns the index and score of up to `k` of the highest scoring words, best first.
+///
+/// The words are split into `threads` contiguous chunks that are scored on their own worker
+/// threads. Ties are always won by the word that comes first in `words`, so the result is the
+/// same no matter how many threads are used.
+pub(crate) fn top_by<F>(words: &[Word], threads: usize, k: usize, score: F) -> Vec<(usize, f64)>
+    where F: Fn(usize, &Word) -> f64 + Sync
+{
+    // the top (index, score)s of a chunk of words starting at index `offset`
+    let top_of = |offset: usize, chunk: &[Word]| {
+        let mut top = Vec::with_capacity(k + 1);
+        for (i, word) in chunk.iter().enumerate() {
+            super::rank_insert(&mut top, k, (offset + i, score(offset + i, word)), |&(_, s)| s);
+        }
+        top
+    };
+
+    if threads <= 1 || words.len() < 2 {
+        return top_of(0, words);
+    }
+
+    let chunk_len = words.len().div_ceil(threads);
+    let top_of = &top_of;
+    let chunk_tops: Vec<Vec<(usize, f64)>> = std::thread::scope(|s| {
+        let handles: Vec<_> = words
+            .chunks(chunk_len)
+            .enumerate()
+            .map(|(c, chunk)| s.spawn(move || top_of(c * chunk_len, chunk)))
+            .collect();
+        handles
+            .into_iter()
+            .map(|h| h.join().expect("scoring thread panicked"))
+            .collect()
+    });
+
+    // merge in chunk order, so an earlier chunk's word stays ahead of an equal later one
+    let mut top = Vec::with_capacity(k + 1);
+    for candidate in chunk_tops.into_iter().flatten() {
+        super::rank_insert(&mut top, k, candidate, |&(_, s)| s);
+    }
+    top
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::PatternTable;
+    use crate::word_list::Word;
+    use super::{buckets, entropy, map, top_by, uncertainty};
+
+    fn words(list: &[(&'static str, usize)]) -> Vec<Word> {
+        list.iter()
+            .enumerate()
+            .map(|(index, &(word, count))| Word { index, word, weight: count as f64 })
+            .collect()
+    }
+
+    #[test]
+    fn table_and_computed_buckets_agree() {
+        let words = words(&[("tares", 5), ("brink", 3), ("speed", 1), ("erase", 2), ("eerie", 1)]);
+        let list: Vec<&str> = words.iter().map(|w| w.word).collect();
+        let table = PatternTable::new(&list);
+        for guess in &words {
+            assert_eq!(buckets(Some(&table), guess, &words), buckets(None, guess, &words));
+        }
+    }
+
+    #[test]
+    fn entropy_of_even_split() {
+        // four equally likely answers that all produce a different pattern give two bits
+        let words = words(&[("abcde", 1), ("abcdf", 1), ("abcfg", 1), ("abfgh", 1)]);
+        let b = buckets(None, &words[0], &words);
+        assert!((entropy(&b, 4.0) - 2.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn uncertainty_of_equal_words() {
+        let words = words(&[("abcde", 3), ("abcdf", 3), ("abcfg", 3), ("abfgh", 3)]);
+        assert!((uncertainty(&words) - 2.0).abs() < 1e-12);
+        assert_eq!(uncertainty(&words[..1]), 0.0);
+    }
+
+    #[test]
+    fn parallel_top_matches_sequential() {
+        let words = words(&[("tares", 5), ("brink", 3), ("speed", 1), ("erase", 2), ("eerie", 1),
+            ("abcde", 2), ("abcdf", 1), ("abcfg", 3), ("abfgh", 1)]);
+        let total = words.iter().map(|w| w.weight).sum();
+        let score = |_, w: &Word| entropy(&buckets(None, w, &words), total);
+        for k in [1, 3, words.len()] {
+            let expected = top_by(&words, 1, k, score);
+            assert_eq!(expected.len(), k);
+            for threads in 2..=12 {
+                assert_eq!(top_by(&words, threads, k, score), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn parallel_map_keeps_the_order() {
+        let words = words(&[("tares", 5), ("brink", 3), ("speed", 1), ("erase", 2), ("eerie", 1)]);
+        let expected: Vec<&str> = words.iter().map(|w| w.word).collect();
+        for threads in 1..=8 {
+            assert_eq!(map(&words, threads, |w| w.word), expected);
+        }
+    }
+
+    #[test]
+    fn ties_go_to_the_first_word() {
+        let words = words(&[("aaaaa", 1), ("bbbbb", 1), ("ccccc", 1), ("ddddd", 1)]);
+        for threads in 1..=4 {
+            assert_eq!(top_by(&words, threads, 2, |_, _| 1.0), vec![(0, 1.0), (1, 1.0)]);
+        }
+        assert!(top_by(&[], 4, 1, |_, _| 1.0).is_empty());
+    }
+}
@@ -3,39 +3,48 @@
 //! winning games in fewer turns since Wordle actually uses more common 5-letter words.
 //!
 use std::borrow::Cow;
-use std::sync::OnceLock;
-use crate::{Guesser, Guess, DICTIONARY, Correctness};
+use super::{Opener, Remaining};
+use crate::{Guess, WordList, Correctness, Prior, Suggester, Suggestion};
+use crate::word_list::Word;
 
-static INITIAL: OnceLock<Vec<(&'static str, usize)>> = OnceLock::new();
 
 pub struct Weight {
-    /// a map containing all possible words that could be a possible solution
-    /// it maps a `word` -> `occurrence count`, where occurrence_count is the number of times
-    /// that word appeared in books
-    // Cow is used because we are either going to be borrowing a Dictionary or we are going to
-    // own a dictionary once we start pruning words
-    remaining: Cow<'static, Vec<(&'static str, usize)>>,
+    /// all possible words that could be a possible solution, along with their prior weight.
+    /// By default the weight is the occurrence count, the number of times that word appeared
+    /// in books
+    remaining: Remaining,
+    /// the first word guessed
+    opener: Opener,
 }
 
 impl Weight {
 
     /// Creates a new Weight algorithm for solving wordle
-    pub fn new() -> Self {
+    pub fn new(words: &'static WordList) -> Self {
         Self {
-            remaining: Cow::Borrowed(INITIAL.get_or_init(|| {
-                Vec::from_iter(
-                    DICTIONARY
-                        .lines()
-                        .map(|line| {
-                            let (word, count) = line
-                                .split_once(' ')
-                                .expect("every line is a word + space + occurrence_count");
-                            let count: usize = count.parse().expect("every count is a number");
-                            (word, count)
-                        }))
-            })),
+            remaining: Remaining::new(words),
+            opener: Opener::default(),
         }
     }
+
+    /// sets the first word to guess, the default is "tares"
+    pub fn with_opener(mut self, opener: Opener) -> Self {
+        self.opener = opener;
+        self
+    }
+
+    /// sets how likely each word is to be the answer, the default is `Prior::Count`
+    pub fn with_prior(mut self, prior: Prior) -> Self {
+        self.remaining.set_prior(prior);
+        self
+    }
+}
+
+impl Default for Weight {
+    /// creates the algorithm for the embedded dictionary
+    fn default() -> Self {
+        Self::new(WordList::dictionary())
+    }
 }
 
 /// Holds the details of a potential best guess
@@ -45,84 +54,81 @@ struct Candidate {
     word: &'static str,
     /// the candidates 'goodness' score, or entropy 'bits'. Higher is better
     goodness: f64,
+    /// the expected amount of information, in bits, gained by guessing the word
+    bits: f64,
+    /// the probability that the word is the answer
+    p_word: f64,
 }
 
-impl Guesser for Weight {
+guess_by_suggesting!(Weight);
 
-    fn guess(&mut self, history: &[Guess]) -> String {
+impl Suggester for Weight {
+    fn rank(&mut self, history: &[Guess], k: usize) -> Vec<Suggestion> {
 
         // prune the dictionary by only keeping words that could be a possible match
         if let Some(last) = history.last() {
-            if matches!(self.remaining, Cow::Owned(_)) {
-                // if the remaining Vec is already owned, just retain the matching words
-                self.remaining
-                    .to_mut()
-                    .retain(|(word, _)| last.matches(word));
-            } else {
-                // else, create a new owned Vec from filtering the matching words
-                self.remaining = Cow::Owned(self.remaining
-                    .iter()
-                    .filter(|(word, _)| last.matches(word))
-                    .copied()
-                    .collect());
-            }
-
+            self.remaining.prune(last);
         }
 
-        // hardcode the first guess to "tares"
+        // the first guess is either a fixed opener, or it is scored like any other guess
         if history.is_empty() {
-            return "tares".to_string();
+            if let Opener::Word(word) = &self.opener {
+                let s = super::suggestion_for(word, self.remaining.iter().map(|w| (w.word, w.weight)), self.remaining.total());
+                return vec![Suggestion { goodness: s.p_word * s.bits, ..s }];
+            }
         }
 
-        // the sum of the counts of all the remaining words in the dictionary
-        let remaining_count: usize = self.remaining
-            .iter()
-            .map(|&(_, c)| c).sum();
-        // the best word
-        let mut best: Option<Candidate> = None;
+        // the sum of the weights of all the remaining words in the dictionary
+        let remaining_weight = self.remaining.weight();
+        // the best k words, best first
+        let mut top: Vec<Candidate> = Vec::with_capacity(k + 1);
 
-        for &(word, count) in &*self.remaining {
+        for &Word { word, weight, .. } in &*self.remaining {
             let mut sum = 0.0;
 
             for pattern in Correctness::patterns() {
-                // total of the count(s) of words that match a pattern
-                let mut in_pattern_total: usize = 0;
+                // total of the weight(s) of words that match a pattern
+                let mut in_pattern_total = 0.0;
 
                 // given a particular candidate word, if we guess this word, what
                 // are the probabilities of getting each pattern. We sum together all those
                 // probabilities and use that to determine the entropy information amount from
                 // guessing that word
-                for &(candidate, count) in &*self.remaining {
+                for candidate in &*self.remaining {
                     // considering a "world" where we did guess "word" and got "pattern" as the
                     // correctness. Now compute what _then_ is left
                     let g = Guess {
                         word: Cow::Borrowed(word),
                         mask: pattern,
                     };
-                    if g.matches(candidate) {
-                        in_pattern_total += count;
+                    if g.matches(candidate.word) {
+                        in_pattern_total += candidate.weight;
                     }
                 }
-                if in_pattern_total == 0 {
+                if in_pattern_total == 0.0 {
                     continue;
                 }
-                let prob_of_this_pattern = in_pattern_total as f64 / remaining_count as f64;
+                let prob_of_this_pattern = in_pattern_total / remaining_weight;
                 sum += prob_of_this_pattern * prob_of_this_pattern.log2()
             }
-            // compute the probability of the current word using its occurrence count
-            let p_word = count as f64 / remaining_count as f64;
+            // compute the probability of the current word using its prior weight
+            let p_word = weight / remaining_weight;
             // negate the sum to get the final goodness amount, a.k.a the entropy "bits"
+            let bits = -sum;
             // factor in the p_word when computing goodness
-            let goodness = p_word * -sum;
+            let goodness = p_word * bits;
 
-            if let Some(c) = best {
-                if goodness > c.goodness {
-                    best = Some(Candidate { word, goodness })
-                }
-            } else {
-                best = Some(Candidate { word, goodness })
-            }
+            super::rank_insert(&mut top, k, Candidate { word, goodness, bits, p_word }, |c| c.goodness);
         }
-        best.unwrap().word.to_string()
+        top.into_iter()
+            .map(|c| Suggestion {
+                word: c.word.to_string(),
+                bits: c.bits,
+                goodness: c.goodness,
+                p_word: c.p_word,
+                remaining: self.remaining.len(),
+                remaining_mass: remaining_weight / self.remaining.total(),
+            })
+            .collect()
     }
 }
\ No newline at end of file
@@ -0,0 +1,91 @@
+//! Command line tool that fits a sigmoid prior, over the frequency rank of the dictionary words,
+//! to the past wordle answers of `answers.txt` by maximum likelihood. The answers are split in
+//! the order wordle used them: the prior is fitted to the older answers and tested on the most
+//! recent ones. The fitted prior is written to a prior file that the `wordle_solver` and `solver`
+//! programs load with `--prior-file`.
+//!
+//! # Running
+//! to fit the prior and write it to `prior.txt`:
+//! ```
+//! > cargo r --release --bin fit_prior
+//! ```
+//! OR to test on the most recent 30% of the answers and write the prior somewhere else
+//! ```
+//! > cargo r --release --bin fit_prior -- --test-fraction 0.3 --output sigmoid.txt
+//! ```
+//! then play the answers with the fitted prior
+//! ```
+//! > cargo r --release --bin solver -- --prior-file prior.txt
+//! ```
+
+use std::path::PathBuf;
+use clap::Parser;
+use wordle_solver::{Prior, WordList};
+use wordle_solver::prior::fit;
+
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct Args {
+    /// the fraction of the answers, the most recent ones, that the fitted prior is tested on
+    #[clap(short, long, default_value_t = 0.2)]
+    test_fraction: f64,
+
+    /// the prior file to write
+    #[clap(short, long, parse(from_os_str), default_value = "prior.txt")]
+    output: PathBuf,
+
+    /// a file of the words that may be guessed, one word per line followed by its occurrence
+    /// count, defaults to the embedded dictionary
+    #[clap(long, parse(from_os_str))]
+    dictionary: Option<PathBuf>,
+
+    /// a file of past answers in the order they were used, oldest first, defaults to the
+    /// embedded past answers. Every answer must be in the dictionary
+    #[clap(long, parse(from_os_str))]
+    answers: Option<PathBuf>,
+}
+
+fn main() -> Result<(), anyhow::Error> {
+    let args = Args::parse();
+
+    let dictionary = WordList::load_or(args.dictionary.as_deref(), WordList::dictionary())?;
+    let answers = WordList::load_or(args.answers.as_deref(), WordList::answers())?;
+    let split = fit::split(dictionary, answers, args.test_fraction)?;
+    if split.train.is_empty() {
+        return Err(anyhow::anyhow!("no answers are left to fit the prior to"));
+    }
+
+    let fitted = fit::fit_sigmoid(dictionary, &split.train);
+    println!(
+        "fitted to the {} oldest answers, tested on the {} most recent",
+        split.train.len(),
+        split.test.len()
+    );
+    println!("mean log-likelihood per answer, higher is better");
+    println!("{:<24}  {:>8}  {:>8}", "prior", "train", "test");
+    for prior in [Prior::Count, Prior::Uniform, Prior::SIGMOID, fitted] {
+        println!(
+            "{:<24}  {:>8.4}  {:>8.4}",
+            prior.to_string(),
+            fit::log_likelihood(&prior, dictionary, &split.train),
+            fit::log_likelihood(&prior, dictionary, &split.test)
+        );
+    }
+
+    let comments = [
+        format!(
+            "sigmoid prior fitted by maximum likelihood to the {} oldest of {} answers",
+            split.train.len(),
+            answers.len()
+        ),
+        format!(
+            "mean log-likelihood per answer: train {:.4}, test {:.4}",
+            fit::log_likelihood(&fitted, dictionary, &split.train),
+            fit::log_likelihood(&fitted, dictionary, &split.test)
+        ),
+    ];
+    fitted.save(&args.output, &comments)
+        .map_err(|e| anyhow::anyhow!("could not write {}: {}", args.output.display(), e))?;
+    println!("wrote {}", args.output.display());
+    Ok(())
+}
@@ -0,0 +1,121 @@
+//! Command line tool that searches for the decision tree with the fewest total guesses for the
+//! answers of `answers.txt`, every answer being equally likely, and compares its average score
+//! to the average score of the prune algorithm on the same answers.
+//!
+//! The exact search is exponential, so by default only the first 100 answers are solved. Use
+//! `--max` to solve more of them, and `--breadth` to only try the most promising guesses of
+//! each set of answers, which makes the score an upper bound of the optimum.
+//!
+//! # Running
+//! to solve the first 100 answers:
+//! ```
+//! > cargo r --release --bin optimal
+//! ```
+//! OR to solve the first 300 answers, guessing any dictionary word
+//! ```
+//! > cargo r --release --bin optimal -- --max 300 --probes
+//! ```
+//! OR to bound the optimum of every answer
+//! ```
+//! > cargo r --release --bin optimal -- --max 5000 --breadth 20
+//! ```
+
+use std::path::PathBuf;
+use clap::Parser;
+use wordle_solver::{Prior, Suggester, WordList, Wordle};
+use wordle_solver::algorithms::{GuessPool, Opener, Optimal, Prune};
+
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct Args {
+    /// the number of answers to solve, from the start of the answers list
+    #[clap(short, long, default_value_t = 100)]
+    max: usize,
+
+    /// guess any dictionary word, even one that can no longer be the answer
+    #[clap(long)]
+    probes: bool,
+
+    /// only try this many of the most promising guesses for each set of answers, the score is
+    /// then an upper bound of the optimum
+    #[clap(short, long)]
+    breadth: Option<usize>,
+
+    /// a file of the words that may be guessed, one word per line followed by an optional
+    /// occurrence count, defaults to the embedded dictionary
+    #[clap(long, parse(from_os_str))]
+    dictionary: Option<PathBuf>,
+
+    /// a file of the answers to solve, one word per line, defaults to the embedded past
+    /// answers. Every answer must be in the dictionary
+    #[clap(long, parse(from_os_str))]
+    answers: Option<PathBuf>,
+
+    /// path of the pattern table cache file, defaults to a file in the system temp directory
+    #[clap(long, parse(from_os_str))]
+    cache: Option<PathBuf>,
+}
+
+fn main() -> Result<(), anyhow::Error> {
+    let args = Args::parse();
+
+    let dictionary = WordList::load_or(args.dictionary.as_deref(), WordList::dictionary())?;
+    let answers = WordList::load_or(args.answers.as_deref(), WordList::answers())?;
+    let cache = args.cache.clone().unwrap_or_else(|| dictionary.default_cache_path());
+    eprintln!("loading pattern table from {}", cache.display());
+    dictionary.load_table(&cache)?;
+
+    let answers: Vec<&'static str> = answers.words().take(args.max).collect();
+    let mut optimal = Optimal::new(dictionary)
+        .with_answers(answers.iter().copied())?
+        .with_probes(args.probes);
+    if let Some(breadth) = args.breadth {
+        optimal = optimal.with_breadth(breadth);
+    }
+    eprintln!("searching the decision tree of {} answers", answers.len());
+    let tree = optimal
+        .solve()
+        .ok_or_else(|| anyhow::anyhow!("there are no answers to solve"))?;
+    let bound = if args.breadth.is_some() { "at most " } else { "" };
+    println!(
+        "optimal: opens with '{}', {}{} guesses in total, average score {}{:.4}, worst score {}",
+        tree.guess,
+        bound,
+        tree.total,
+        bound,
+        tree.average(),
+        tree.depth()
+    );
+
+    // prune only considers the same answers, equally likely, and computes its opener
+    let text: String = answers.iter().map(|a| format!("{}\n", a)).collect();
+    let restricted: &'static WordList = Box::leak(Box::new(WordList::parse("answers", String::leak(text))?));
+    let pool = if args.probes { GuessPool::Dictionary } else { GuessPool::Remaining };
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let prune = |opener| {
+        Prune::new(dictionary)
+            .with_opener(opener)
+            .with_pool(pool)
+            .with_threads(threads)
+            .with_prior(Prior::Uniform)
+            .with_answers(restricted)
+    };
+    // the computed opener is the same in every game, so compute it only once
+    let opener = Opener::Word(prune(Opener::Compute).try_suggest(&[])?.word);
+    let w = Wordle::new(dictionary).with_max_rounds(usize::MAX);
+    let mut total = 0;
+    let mut worst = 0;
+    for &answer in &answers {
+        let prune = prune(opener.clone());
+        let score = w.try_play(answer, prune)?.unwrap_or(usize::MAX);
+        total += score;
+        worst = worst.max(score);
+    }
+    println!(
+        "prune:   {} guesses in total, average score {:.4}, worst score {}",
+        total,
+        total as f64 / answers.len() as f64,
+        worst
+    );
+    Ok(())
+}
@@ -1,22 +1,68 @@
-/// Command line driven wordle solver that runs a Guesser
-/// against each answer in `answers.txt` and prints out how many
-/// turns it took to guess each answer. There are currently 2309
-/// words in the answer.txt file.
-///
-/// # Running
-/// to run solver using the default (fastest) algorithm, 'prune':
-/// ```
-/// > cargo r --release --bin solver
-/// ```
-/// OR to see a list of options
-/// ```
-/// > cargo r --release --bin solver -- --help
-/// ```
+//! Command line driven wordle solver that runs a Guesser
+//! against each answer in `answers.txt` and prints out how many
+//! turns it took to guess each answer. There are currently 2309
+//! words in the answer.txt file. Use `--answers` to play the answers of
+//! another file, and `--dictionary` to guess the words of another dictionary.
+//!
+//! # Running
+//! to run solver using the default (fastest) algorithm, 'prune':
+//! ```
+//! > cargo r --release --bin solver
+//! ```
+//! OR to open every game with "salet" instead of "tares"
+//! ```
+//! > cargo r --release --bin solver -- --opener salet
+//! ```
+//! OR to compare the openers listed in a file, one benchmark per opener
+//! ```
+//! > cargo r --release --bin solver -- --sweep --openers openers.txt
+//! ```
+//! OR to print every guess of each game
+//! ```
+//! > cargo r --release --bin solver -- --details
+//! ```
+//! OR to only consider past answers as possible answers, guessing any dictionary word
+//! ```
+//! > cargo r --release --bin solver -- --restrict --probes
+//! ```
+//! OR to treat the 3000 or so most common words as about equally likely answers
+//! ```
+//! > cargo r --release --bin solver -- --prior sigmoid
+//! ```
+//! OR to use the prior fitted to past answers by the fit_prior program
+//! ```
+//! > cargo r --release --bin solver -- --prior-file prior.txt
+//! ```
+//! OR to guess the word that minimizes the expected number of guesses
+//! ```
+//! > cargo r --release --bin solver -- --implementation expected-turns
+//! ```
+//! OR to look two guesses ahead from the 20 best single-step guesses
+//! ```
+//! > cargo r --release --bin solver -- --implementation lookahead --beam 20
+//! ```
+//! OR to bound the worst case by guessing the word whose largest pattern bucket is smallest
+//! ```
+//! > cargo r --release --bin solver -- --implementation minimax --probes
+//! ```
+//! OR to play 4 games at a time
+//! ```
+//! > cargo r --release --bin solver -- --jobs 4
+//! ```
+//! OR to see a list of options
+//! ```
+//! > cargo r --release --bin solver -- --help
+//! ```
 
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
 use clap::{ArgEnum, Parser};
-use wordle_solver::{Guesser};
-
-const GAMES: &str = include_str!("../../answers.txt");
+use std::path::Path;
+use anyhow::anyhow;
+use wordle_solver::{Correctness, GameRecord, Outcome, Prior, Suggester, WordList, Wordle};
+use wordle_solver::algorithms::{ExpectedTurns, GuessPool, Lookahead, Minimax, Opener, Prune, Scoring, TurnsEstimator};
 
 #[derive(Parser, Debug)]
 #[clap(author, version, about, long_about = None)]
@@ -28,6 +74,100 @@ struct Args {
     /// max Number of games to play
     #[clap(short, long)]
     max: Option<usize>,
+
+    /// the first word to guess in every game, or 'compute' to score every word of the dictionary
+    /// and open with the best one
+    #[clap(short, long, default_value = "tares")]
+    opener: Opener,
+
+    /// play by the NYT hard mode rules, the prune implementation only guesses words that use all
+    /// revealed hints, and any guess that does not stops the benchmark with a report of the rule
+    /// that was broken
+    #[clap(long)]
+    hard: bool,
+
+    /// the number of guesses a guesser gets before a game is lost
+    #[clap(long, default_value_t = Wordle::DEFAULT_MAX_ROUNDS)]
+    max_rounds: usize,
+
+    /// a game solved within this many guesses is a win
+    #[clap(long, default_value_t = Wordle::OFFICIAL_ROUNDS)]
+    win_rounds: usize,
+
+    /// print every guess of each game, with its mask, the number of words that could still be
+    /// the answer and how long the guess took
+    #[clap(short, long)]
+    details: bool,
+
+    /// instead of playing one opener, play every answer with each opener of a list and report
+    /// their average scores, from best to worst
+    #[clap(long)]
+    sweep: bool,
+
+    /// a file of whitespace separated openers to sweep, defaults to every dictionary word
+    #[clap(long, parse(from_os_str), requires = "sweep")]
+    openers: Option<PathBuf>,
+
+    /// number of games to play at the same time, each on its own thread
+    #[clap(short, long, default_value_t = 1)]
+    jobs: usize,
+
+    /// how the prune implementation scores candidate words
+    #[clap(short, long, arg_enum, default_value_t = ScoringArg::Buckets)]
+    scoring: ScoringArg,
+
+    /// let the prune implementation guess any dictionary word, even one that can no longer be
+    /// the answer, when it splits the remaining words better (normal mode probe guesses)
+    #[clap(long)]
+    probes: bool,
+
+    /// number of threads used to score candidate words, defaults to the number of CPU cores
+    #[clap(short, long)]
+    threads: Option<usize>,
+
+    /// a file of the words that may be guessed, one word per line followed by an optional
+    /// occurrence count, defaults to the embedded dictionary
+    #[clap(long, parse(from_os_str))]
+    dictionary: Option<PathBuf>,
+
+    /// a file of the answers to play, one word per line, defaults to the embedded past answers.
+    /// Every answer must be in the dictionary
+    #[clap(long, parse(from_os_str))]
+    answers: Option<PathBuf>,
+
+    /// let the prune, expected-turns, lookahead or minimax implementation only consider the
+    /// answers list as possible answers, while any dictionary word can still be guessed as a probe
+    #[clap(long)]
+    restrict: bool,
+
+    /// how likely each word is to be the answer, for the weight and prune implementations:
+    /// 'count' (occurrence count), 'uniform', 'sigmoid' (over frequency rank) or
+    /// 'sigmoid:<midpoint>:<width>'
+    #[clap(long, default_value = "count")]
+    prior: Prior,
+
+    /// a prior file to read the prior from instead of --prior, such as the one written by
+    /// the fit_prior program
+    #[clap(long, parse(from_os_str), conflicts_with = "prior")]
+    prior_file: Option<PathBuf>,
+
+    /// the number of best single-step guesses the lookahead implementation looks two guesses
+    /// ahead from
+    #[clap(long, default_value_t = Lookahead::DEFAULT_BEAM)]
+    beam: usize,
+
+    /// learn the expected-turns estimator from games of prune played with the pool, --restrict
+    /// and --hard settings of this run, instead of using the estimator learned from the default
+    /// games. It is always learned again when --prior, --prior-file, --dictionary or --answers
+    /// is given
+    #[clap(long)]
+    learn: bool,
+
+    /// path of the pattern table cache file, defaults to a file in the system temp directory
+    /// named after the dictionary. Only the prune, expected-turns, lookahead and minimax
+    /// implementations load the table
+    #[clap(long, parse(from_os_str))]
+    cache: Option<PathBuf>,
 }
 
 /// various Wordle guesser implementations
@@ -39,56 +179,447 @@ enum Implementation {
     Once,
     Precalc,
     Weight,
-    Prune
+    Prune,
+    ExpectedTurns,
+    Lookahead,
+    Minimax,
 }
 
+impl Implementation {
+    /// returns `true` if the implementation can restrict the possible answers, see --restrict
+    fn restricts(self) -> bool {
+        matches!(self, Implementation::Prune | Implementation::ExpectedTurns | Implementation::Lookahead | Implementation::Minimax)
+    }
+
+    /// returns `true` if the implementation reads the pattern table, see --cache
+    fn uses_table(self) -> bool {
+        matches!(self, Implementation::Prune | Implementation::ExpectedTurns | Implementation::Lookahead | Implementation::Minimax)
+    }
+}
 
+/// the ways the Prune implementation can score candidate words
+#[derive(ArgEnum, Debug, Copy, Clone)]
+enum ScoringArg {
+    /// iterate every correctness pattern that was not pruned yet
+    Patterns,
+    /// accumulate the pattern of every candidate into 243 buckets, suggesting the same words
+    /// faster
+    Buckets,
+}
 
-fn main() {
+fn main() -> Result<(), anyhow::Error> {
     let args = Args::parse();
 
+    let dictionary = WordList::load_or(args.dictionary.as_deref(), WordList::dictionary())?;
+    let answers = WordList::load_or(args.answers.as_deref(), WordList::answers())?;
+    if let Some(answer) = answers.words().find(|&w| !dictionary.contains(w)) {
+        return Err(anyhow!("answer '{}' is not in the dictionary", answer));
+    }
+
+    // the older implementations never read the table, so they don't pay for building it
+    if args.implementation.uses_table() {
+        let cache = args.cache.clone().unwrap_or_else(|| dictionary.default_cache_path());
+        eprintln!("loading pattern table from {}", cache.display());
+        dictionary.load_table(&cache)?;
+    }
+    let threads = args.threads.unwrap_or_else(|| {
+        std::thread::available_parallelism().map_or(1, |n| n.get())
+    });
+    let pool = if args.hard {
+        GuessPool::Hard
+    } else if args.probes {
+        GuessPool::Dictionary
+    } else {
+        GuessPool::Remaining
+    };
+
+    let prior = match &args.prior_file {
+        Some(path) => Prior::load(path)?,
+        None => args.prior,
+    };
+
+    let restrict = args.restrict;
+    if restrict && !args.implementation.restricts() {
+        return Err(anyhow::anyhow!("--restrict is only supported by the prune, expected-turns, lookahead and minimax implementations"));
+    }
     match args.implementation {
         Implementation::Unoptimized => {
-            play(wordle_solver::algorithms::Unoptimized::new, args.max);
+            run(|o| wordle_solver::algorithms::Unoptimized::new(dictionary).with_opener(o), &args, dictionary, answers)?;
         },
         Implementation::Allocs => {
-            play(wordle_solver::algorithms::Allocs::new, args.max);
+            run(|o| wordle_solver::algorithms::Allocs::new(dictionary).with_opener(o), &args, dictionary, answers)?;
         },
         Implementation::Vecrem => {
-            play(wordle_solver::algorithms::Vecrem::new, args.max);
+            run(|o| wordle_solver::algorithms::Vecrem::new(dictionary).with_opener(o), &args, dictionary, answers)?;
         },
         Implementation::Once => {
-            play(wordle_solver::algorithms::OnceInit::new, args.max);
+            run(|o| wordle_solver::algorithms::OnceInit::new(dictionary).with_opener(o), &args, dictionary, answers)?;
         },
         Implementation::Precalc => {
-            play(wordle_solver::algorithms::PreCalc::new, args.max);
+            run(|o| wordle_solver::algorithms::PreCalc::new(dictionary).with_opener(o), &args, dictionary, answers)?;
         },
         Implementation::Weight => {
-            play(wordle_solver::algorithms::Weight::new, args.max);
+            run(|o| wordle_solver::algorithms::Weight::new(dictionary).with_opener(o).with_prior(prior), &args, dictionary, answers)?;
         },
         // run prune by default
         Implementation::Prune => {
-            play(wordle_solver::algorithms::Prune::new, args.max);
+            let scoring = match args.scoring {
+                ScoringArg::Patterns => Scoring::Patterns,
+                ScoringArg::Buckets => Scoring::Buckets,
+            };
+            let prune = |o| {
+                let prune = Prune::new(dictionary)
+                    .with_opener(o)
+                    .with_scoring(scoring)
+                    .with_pool(pool)
+                    .with_threads(threads)
+                    .with_prior(prior);
+                if restrict {
+                    prune.with_answers(answers)
+                } else {
+                    prune
+                }
+            };
+            run(prune, &args, dictionary, answers)?;
         },
+        Implementation::ExpectedTurns => {
+            // the default estimator was learned from games of the default prune, on the embedded
+            // dictionary and answers
+            let learn = args.learn
+                || args.dictionary.is_some()
+                || args.answers.is_some()
+                || args.prior_file.is_some()
+                || prior != Prior::Count;
+            let estimator = if learn {
+                eprintln!("learning how many guesses games take from {} games of prune", answers.len());
+                let prune = || {
+                    let prune = Prune::new(dictionary)
+                        .with_pool(pool)
+                        .with_threads(threads)
+                        .with_prior(prior);
+                    if restrict {
+                        prune.with_answers(answers)
+                    } else {
+                        prune
+                    }
+                };
+                TurnsEstimator::simulate(&Wordle::new(dictionary).with_hard_mode(args.hard), answers, prune)?
+            } else {
+                TurnsEstimator::default()
+            };
+            let expected_turns = |o| {
+                let expected_turns = ExpectedTurns::new(dictionary)
+                    .with_opener(o)
+                    .with_pool(pool)
+                    .with_threads(threads)
+                    .with_prior(prior)
+                    .with_estimator(estimator.clone());
+                if restrict {
+                    expected_turns.with_answers(answers)
+                } else {
+                    expected_turns
+                }
+            };
+            run(expected_turns, &args, dictionary, answers)?;
+        },
+        Implementation::Lookahead => {
+            let beam = args.beam;
+            let lookahead = |o| {
+                let lookahead = Lookahead::new(dictionary)
+                    .with_opener(o)
+                    .with_pool(pool)
+                    .with_threads(threads)
+                    .with_prior(prior)
+                    .with_beam(beam);
+                if restrict {
+                    lookahead.with_answers(answers)
+                } else {
+                    lookahead
+                }
+            };
+            run(lookahead, &args, dictionary, answers)?;
+        },
+        Implementation::Minimax => {
+            let minimax = |o| {
+                let minimax = Minimax::new(dictionary)
+                    .with_opener(o)
+                    .with_pool(pool)
+                    .with_threads(threads)
+                    .with_prior(prior);
+                if restrict {
+                    minimax.with_answers(answers)
+                } else {
+                    minimax
+                }
+            };
+            run(minimax, &args, dictionary, answers)?;
+        },
+    }
+    Ok(())
+}
+
+
+
+/// plays the `answers` with guessers created by `maker`, either with the opener or as a
+/// sweep of many openers, depending on `args`
+fn run<G>(
+    maker: impl Fn(Opener) -> G + Sync,
+    args: &Args,
+    dictionary: &'static WordList,
+    answers: &'static WordList,
+) -> Result<(), anyhow::Error>
+    where G: Suggester
+{
+    let answers: Vec<&'static str> = answers
+        .words()
+        .take(args.max.unwrap_or(usize::MAX))
+        .collect();
+    let w = Wordle::new(dictionary)
+        .with_hard_mode(args.hard)
+        .with_max_rounds(args.max_rounds)
+        .with_win_rounds(args.win_rounds);
+    if !args.sweep {
+        if let Opener::Word(word) = &args.opener {
+            if !w.contains(word) {
+                return Err(anyhow!("opener '{}' is not in the dictionary", word));
+            }
+        }
+        return play(&w, maker, args.opener.clone(), &answers, args.jobs, args.details);
     }
+
+    let openers = match &args.openers {
+        Some(path) => read_openers(&w, path)?,
+        None => {
+            let mut words: Vec<String> = w.words().map(str::to_string).collect();
+            words.sort_unstable();
+            words
+        }
+    };
+    sweep(&w, maker, openers, &answers, args.jobs)
 }
 
+/// reads a file of whitespace separated openers, every opener must be a dictionary word
+fn read_openers(w: &Wordle, path: &Path) -> Result<Vec<String>, anyhow::Error> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| anyhow!("could not read openers from {}: {}", path.display(), e))?;
+    contents
+        .split_whitespace()
+        .map(|word| match word.parse()? {
+            Opener::Word(word) if w.contains(&word) => Ok(word),
+            _ => Err(anyhow!("opener '{}' is not in the dictionary", word)),
+        })
+        .collect()
+}
 
+/// plays a game for each of the `answers`, `maker` creates a guesser that opens with the
+/// given opener.
+/// The games are distributed across `jobs` threads, but their results are always printed in the
+/// order of the answers. When `details` is `true` every guess of a game is printed.
+/// Returns an error if a game could not be played
+fn play<G>(w: &Wordle, maker: impl Fn(Opener) -> G + Sync, opener: Opener, answers: &[&'static str], jobs: usize, details: bool)
+    -> Result<(), anyhow::Error>
+    where G: Suggester
+{
+    let opener = resolve(&maker, opener)?;
 
-/// plays multiple games using previous answers
-fn play<G>(mut maker: impl FnMut() -> G, max: Option<usize>) where G: Guesser {
-    let w = wordle_solver::Wordle::new();
-    let mut score = 0;
-    let mut games = 0;
-    for answer in GAMES.split_whitespace().take(max.unwrap_or(usize::MAX)) {
-        let guesser = (maker)();
-        if let Some(s) = w.play(answer, guesser) {
-            games += 1;
-            score += s;
-            println!("guessed '{}' in {}", &answer, s);
+    let summary = play_all(w, &maker, &opener, answers, jobs, |record| {
+        if let Some(s) = record.score() {
+            println!("guessed '{}' in {}", record.answer, s);
         } else {
             eprintln!("failed to guess..zoinks!");
         }
+        if details {
+            for (round, turn) in record.turns.iter().enumerate() {
+                let remaining = turn
+                    .remaining
+                    .map_or_else(|| "?".to_string(), |r| r.to_string());
+                println!(
+                    "  {:>2} {} {} {:>5} remaining {:>10.3?}",
+                    round + 1,
+                    turn.word,
+                    Correctness::to_str(&turn.mask),
+                    remaining,
+                    turn.elapsed
+                );
+            }
+        }
+    })?;
+    match summary.average() {
+        Some(average) => println!("average score {:.2}", average),
+        None => println!("average score n/a, no game was solved"),
+    }
+    println!(
+        "won {} of {} games ({:.2}%), {} solved late, {} unsolved",
+        summary.wins,
+        summary.played(),
+        summary.win_rate() * 100.0,
+        summary.failures - summary.unsolved,
+        summary.unsolved
+    );
+    if summary.unsolved == 0 {
+        println!("worst score {}", summary.worst);
+    }
+    Ok(())
+}
+
+/// plays every one of the `answers` once for each of the `openers`, and prints the openers sorted from the
+/// best to the worst average score. Returns an error if a game could not be played
+fn sweep<G>(w: &Wordle, maker: impl Fn(Opener) -> G + Sync, openers: Vec<String>, answers: &[&'static str], jobs: usize)
+    -> Result<(), anyhow::Error>
+    where G: Suggester
+{
+    let mut results = Vec::with_capacity(openers.len());
+    for (i, word) in openers.into_iter().enumerate() {
+        let opener = Opener::Word(word.clone());
+        let summary = play_all(w, &maker, &opener, answers, jobs, |_| {})?;
+        eprintln!("{:>5} {} average {} won {:.2}%", i + 1, word, format_average(&summary), summary.win_rate() * 100.0);
+        results.push((word, summary));
+    }
+
+    results.sort_by(|(a_word, a), (b_word, b)| {
+        // openers that solved no game sort last
+        let (a_average, b_average) = (a.average().unwrap_or(f64::INFINITY), b.average().unwrap_or(f64::INFINITY));
+        a_average
+            .total_cmp(&b_average)
+            .then(a.failures.cmp(&b.failures))
+            .then(a.worst.cmp(&b.worst))
+            .then(a_word.cmp(b_word))
+    });
+    println!("opener  average  win rate  failures  worst");
+    for (word, summary) in results {
+        let worst = if summary.unsolved > 0 {
+            "unsolved".to_string()
+        } else {
+            summary.worst.to_string()
+        };
+        println!(
+            "{:<6}  {:>7}  {:>7.2}%  {:>8}  {:>5}",
+            word,
+            format_average(&summary),
+            summary.win_rate() * 100.0,
+            summary.failures,
+            worst
+        );
+    }
+    Ok(())
+}
+
+/// formats the average score of `summary` with four decimals, or "n/a" if no game was solved
+fn format_average(summary: &Summary) -> String {
+    summary.average().map_or_else(|| "n/a".to_string(), |average| format!("{:.4}", average))
+}
+
+/// a computed opener is the same in every game, so resolve it to a word only once
+fn resolve<G: Suggester>(maker: &impl Fn(Opener) -> G, opener: Opener) -> Result<Opener, anyhow::Error> {
+    match opener {
+        Opener::Compute => {
+            let word = (maker)(Opener::Compute).try_suggest(&[])?.word;
+            eprintln!("computed opener '{}'", word);
+            Ok(Opener::Word(word))
+        }
+        opener => Ok(opener),
+    }
+}
+
+/// The scores of playing a list of answers
+#[derive(Debug, Default)]
+struct Summary {
+    /// number of games that were solved
+    games: usize,
+    /// the total number of rounds of all solved games
+    score: usize,
+    /// number of games that were won
+    wins: usize,
+    /// number of games that were not won, because they took too many guesses or were not
+    /// solved at all
+    failures: usize,
+    /// the most rounds any solved game took
+    worst: usize,
+    /// number of games that were not solved
+    unsolved: usize,
+}
+
+impl Summary {
+    /// the average number of rounds of the solved games, `None` if no game was solved
+    fn average(&self) -> Option<f64> {
+        (self.games > 0).then(|| self.score as f64 / self.games as f64)
+    }
+
+    /// the number of games played, solved or not
+    fn played(&self) -> usize {
+        self.games + self.unsolved
     }
-    println!("average score {:.2}", score as f64 / games as f64 );
-}
\ No newline at end of file
+
+    /// the fraction of all played games that were won
+    fn win_rate(&self) -> f64 {
+        self.wins as f64 / self.played() as f64
+    }
+}
+
+/// plays a game for each of the `answers`, using a guesser created by `maker` that opens with
+/// `opener`. `report` is called with the record of every game, in answer order.
+/// The games are distributed across `jobs` threads. Stops at the first game that could not be
+/// played, such as an answer that is not in the dictionary, and returns its error
+fn play_all<G>(
+    w: &Wordle,
+    maker: &(impl Fn(Opener) -> G + Sync),
+    opener: &Opener,
+    answers: &[&'static str],
+    jobs: usize,
+    mut report: impl FnMut(&GameRecord),
+) -> Result<Summary, anyhow::Error>
+    where G: Suggester
+{
+    // index of the next answer a job should play
+    let next = AtomicUsize::new(0);
+    let (tx, rx) = mpsc::channel();
+
+    let mut summary = Summary::default();
+    std::thread::scope(|s| {
+        for _ in 0..jobs.max(1) {
+            let tx = tx.clone();
+            let next = &next;
+            s.spawn(move || loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                let Some(&answer) = answers.get(i) else {
+                    break;
+                };
+                let guesser = (maker)(opener.clone());
+                let record = w
+                    .try_record(answer, guesser)
+                    .map_err(|e| anyhow!("could not play '{}': {}", answer, e));
+                if tx.send((i, record)).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(tx);
+
+        // results arrive in any order, buffer them until they can be reported in answer order
+        let mut finished = BTreeMap::new();
+        let mut reported = 0;
+        for (i, record) in rx {
+            finished.insert(i, record);
+            while let Some(record) = finished.remove(&reported) {
+                // stop handing out answers, the jobs stop once they can't send their records
+                let record = record.inspect_err(|_| next.store(answers.len(), Ordering::Relaxed))?;
+                match record.outcome {
+                    Outcome::Won(_) => summary.wins += 1,
+                    Outcome::SolvedLate(_) => summary.failures += 1,
+                    Outcome::Unsolved => {
+                        summary.unsolved += 1;
+                        summary.failures += 1;
+                    }
+                }
+                if let Some(s) = record.score() {
+                    summary.games += 1;
+                    summary.score += s;
+                    summary.worst = summary.worst.max(s);
+                }
+                report(&record);
+                reported += 1;
+            }
+        }
+        Ok(summary)
+    })
+}
@@ -0,0 +1,167 @@
+//! Explains why a history of guesses matches no word of the dictionary.
+//!
+//! A history is contradictory when no word could have produced every mask in it, which almost
+//! always means a mask was mistyped. The smallest set of guesses that can't be true together is
+//! searched for, first single guesses and then pairs, and the masks of those guesses are then
+//! edited one tile at a time to find the nearest history that is consistent again.
+//!
+use std::borrow::Cow;
+use std::fmt;
+use crate::{Correctness, Guess};
+
+/// The guesses of a history that contradict each other, and the mask edit that would fix them
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Contradiction {
+    /// indices into the history of the guesses that no word matches together, in order.
+    /// This is a single guess or a pair when possible, otherwise it is every guess up to and
+    /// including the one that made the history contradictory
+    pub turns: Vec<usize>,
+    /// the nearest edit of a single tile of a mask that makes the history consistent, if any
+    pub fix: Option<MaskFix>,
+}
+
+/// A corrected mask for one of the guesses of a history
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct MaskFix {
+    /// index into the history of the guess whose mask was mistyped
+    pub turn: usize,
+    /// the corrected mask, it differs from the mistyped one in a single tile
+    pub mask: [Correctness; 5],
+}
+
+impl fmt::Display for Contradiction {
+    /// turns are displayed starting at one, the way the interactive CLI numbers them
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(fix) = &self.fix {
+            return write!(f, "turn {} looks mistyped, did you mean {}?", fix.turn + 1, Correctness::to_str(&fix.mask));
+        }
+        match self.turns[..] {
+            [turn] => write!(f, "turn {} matches no word of the dictionary", turn + 1),
+            [a, b] => write!(f, "turns {} and {} contradict each other", a + 1, b + 1),
+            _ => write!(f, "turns 1 to {} contradict each other", self.turns.len()),
+        }
+    }
+}
+
+/// checks whether any of the `words` matches every guess of `history`. Returns `None` if one
+/// does, otherwise the guesses that contradict each other and the nearest fix of their masks
+pub fn find(words: &[&str], history: &[Guess]) -> Option<Contradiction> {
+    let all: Vec<usize> = (0..history.len()).collect();
+    if count_matches(words, history, &all, None) > 0 {
+        return None;
+    }
+
+    let singles = (0..history.len()).map(|t| vec![t]);
+    let pairs = (0..history.len())
+        .flat_map(|a| (a + 1..history.len()).map(move |b| vec![a, b]));
+    let turns = singles
+        .chain(pairs)
+        .find(|turns| count_matches(words, history, turns, None) == 0)
+        .unwrap_or_else(|| {
+            // the first prefix of the history that no word matches
+            let end = (1..=history.len())
+                .find(|&end| count_matches(words, history, &all[..end], None) == 0)
+                .unwrap_or(history.len());
+            all[..end].to_vec()
+        });
+
+    // the contradiction is within `turns`, so only their masks need to be edited. The edit
+    // that leaves the most words is the most plausible typo, later turns win ties
+    let mut fix: Option<(MaskFix, usize)> = None;
+    for &turn in turns.iter().rev() {
+        for position in 0..5 {
+            for c in [Correctness::Correct, Correctness::Misplaced, Correctness::Wrong] {
+                if history[turn].mask[position] == c {
+                    continue;
+                }
+                let mut mask = history[turn].mask;
+                mask[position] = c;
+                let edit = MaskFix { turn, mask };
+                let matches = count_matches(words, history, &all, Some(&edit));
+                if matches > fix.map_or(0, |(_, best)| best) {
+                    fix = Some((edit, matches));
+                }
+            }
+        }
+    }
+
+    Some(Contradiction {
+        turns,
+        fix: fix.map(|(fix, _)| fix),
+    })
+}
+
+/// counts the `words` that match the guesses of `history` at the indices in `turns`, with the
+/// mask of one guess replaced by `edit`
+fn count_matches(words: &[&str], history: &[Guess], turns: &[usize], edit: Option<&MaskFix>) -> usize {
+    let guesses: Vec<Guess> = turns
+        .iter()
+        .map(|&turn| Guess {
+            word: Cow::Borrowed(&history[turn].word),
+            mask: match edit {
+                Some(edit) if edit.turn == turn => edit.mask,
+                _ => history[turn].mask,
+            },
+        })
+        .collect();
+    words
+        .iter()
+        .filter(|word| guesses.iter().all(|g| g.matches(word)))
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+    use crate::{Correctness, Guess, Wordle};
+    use super::find;
+
+    fn guess(word: &'static str, mask: &str) -> Guess<'static> {
+        Guess { word: Cow::Borrowed(word), mask: Correctness::try_from_str(mask).unwrap() }
+    }
+
+    fn words() -> Vec<&'static str> {
+        Wordle::default().words().collect()
+    }
+
+    #[test]
+    fn consistent_history_has_no_contradiction() {
+        let history = [guess("tares", "wwwmw"), guess("biome", "wwwwc")];
+        assert_eq!(find(&words(), &history), None);
+    }
+
+    #[test]
+    fn impossible_mask_is_the_culprit() {
+        // every letter of tares is placed, so there is nowhere left for the yellow s
+        let history = [guess("lucky", "wwwww"), guess("tares", "ccccm")];
+        let words = words();
+        let c = find(&words, &history).unwrap();
+        assert_eq!(c.turns, vec![1]);
+        let fix = c.fix.unwrap();
+        assert_eq!(fix.turn, 1);
+        assert_eq!(fix.mask.iter().zip(&history[1].mask).filter(|(a, b)| a != b).count(), 1);
+        assert!(c.to_string().starts_with("turn 2 looks mistyped, did you mean "));
+
+        let mut fixed = history;
+        fixed[1].mask = fix.mask;
+        assert_eq!(find(&words, &fixed), None);
+    }
+
+    #[test]
+    fn contradicting_pair_is_found() {
+        // the t is green in the first guess, but gray in the third
+        let history = [
+            guess("tares", "cwwww"),
+            guess("lucky", "wwwww"),
+            guess("tepid", "wwwww"),
+        ];
+        let words = words();
+        let c = find(&words, &history).unwrap();
+        assert_eq!(c.turns, vec![0, 2]);
+
+        let fix = c.fix.unwrap();
+        let mut fixed = history;
+        fixed[fix.turn].mask = fix.mask;
+        assert_eq!(find(&words, &fixed), None);
+    }
+}
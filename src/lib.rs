@@ -1,65 +1,226 @@
 use std::borrow::Cow;
-use std::collections::HashSet;
+use std::fmt;
+use std::time::{Duration, Instant};
 use anyhow::anyhow;
 
 pub mod algorithms;
+pub mod contradiction;
+pub mod parser;
+pub mod pattern;
+pub mod prior;
+pub mod word_list;
 
-/// list of all 5 letter words
-const DICTIONARY: &str = include_str!("../dictionary.txt");
+pub use contradiction::Contradiction;
+pub use pattern::{PatternId, PatternTable};
+pub use prior::Prior;
+pub use word_list::WordList;
 
 pub struct Wordle {
-    dictionary: HashSet<&'static str>,
+    /// the words that may be guessed
+    dictionary: &'static WordList,
+    /// when `true`, every guess must use all the hints revealed by previous guesses
+    hard_mode: bool,
+    /// the number of guesses a guesser gets before the game is over
+    max_rounds: usize,
+    /// a game solved in this many rounds or less is a win
+    win_rounds: usize,
 }
 
 impl Wordle {
-    pub fn new() -> Self {
+    /// creates a game that accepts the words of `dictionary` as guesses
+    pub fn new(dictionary: &'static WordList) -> Self {
         Self {
-            // step by 2 because every other token in Dictionary is a words frequency count
-            dictionary: HashSet::from_iter(
-                DICTIONARY
-                    .lines()
-                    .map(|line| {
-                        line.split_once(' ')
-                            .expect("every line is a word + space + occurrence_count")
-                            .0
-                    })),
+            dictionary,
+            hard_mode: false,
+            max_rounds: Self::DEFAULT_MAX_ROUNDS,
+            win_rounds: Self::OFFICIAL_ROUNDS,
         }
     }
 
+    /// the number of guesses allowed by the official rules
+    pub const OFFICIAL_ROUNDS: usize = 6;
+
+    /// wordle only allows six guesses, but by default we'll allow more chances in order to avoid
+    /// chopping off the score distribution for stats purposes
+    pub const DEFAULT_MAX_ROUNDS: usize = 32;
+
+    /// enables or disables NYT "hard mode", where any revealed hints must be used in later
+    /// guesses. Hard mode is disabled by default
+    pub fn with_hard_mode(mut self, hard_mode: bool) -> Self {
+        self.hard_mode = hard_mode;
+        self
+    }
+
+    /// sets the number of guesses a guesser gets before the game is over, the default is
+    /// `DEFAULT_MAX_ROUNDS`
+    pub fn with_max_rounds(mut self, max_rounds: usize) -> Self {
+        self.max_rounds = max_rounds;
+        self
+    }
+
+    /// sets the number of rounds a game must be solved within to count as a win, the default is
+    /// `OFFICIAL_ROUNDS`
+    pub fn with_win_rounds(mut self, win_rounds: usize) -> Self {
+        self.win_rounds = win_rounds;
+        self
+    }
+
+    /// returns `true` if `word` is in the dictionary, and so is a valid guess
+    pub fn contains(&self, word: &str) -> bool {
+        self.dictionary.contains(word)
+    }
+
+    /// returns an iterator over every word in the dictionary, in dictionary order
+    pub fn words(&self) -> impl Iterator<Item=&'static str> + '_ {
+        self.dictionary.words()
+    }
+
     /// plays a game of wordle using the provided `guesser` to guess the `answer`
     /// returns `Some(round_number)` if the answer was guessed, else `None` if the guesser
-    /// could not guess the answer
-    pub fn play<G: Guesser>(&self, answer: &'static str, mut guesser: G) -> Option<usize> {
+    /// could not guess the answer within the maximum number of rounds
+    ///
+    /// # Panics
+    /// if `try_play` returns an error, e.g. the guesser guesses a word that is not in the
+    /// dictionary, or a word that breaks the hard mode rules when hard mode is enabled
+    pub fn play<G: Guesser>(&self, answer: &'static str, guesser: G) -> Option<usize> {
+        self.try_play(answer, guesser)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// plays a game of wordle using the provided `guesser` to guess the `answer`, like `play`,
+    /// but returns an error instead of panicking when the answer or a guess is not a dictionary
+    /// word, a guess breaks the hard mode rules, or the guesser fails to make a guess
+    pub fn try_play<G: Guesser>(&self, answer: &'static str, guesser: G) -> Result<Option<usize>, Error> {
+        self.try_record(answer, guesser).map(|record| record.score())
+    }
+
+    /// plays a game of wordle like `play`, but returns a record of every guess of the game
+    ///
+    /// # Panics
+    /// if `try_record` returns an error, see `try_play`
+    pub fn record<G: Guesser>(&self, answer: &'static str, guesser: G) -> GameRecord {
+        self.try_record(answer, guesser)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// plays a game of wordle like `try_play`, but returns a record of every guess of the game
+    pub fn try_record<G: Guesser>(&self, answer: &'static str, mut guesser: G) -> Result<GameRecord, Error> {
+        self.check_word(answer)?;
 
         // stores past guesses
         let mut history = Vec::new();
+        let mut record = GameRecord { answer, turns: Vec::new(), outcome: Outcome::Unsolved };
 
-        // wordle only allows six guesses, we'll allow more chances in order to avoid
-        // chopping off the score distribution for stats purposes
-        for i in 1..=32 {
-            let guess = guesser.guess(&history[..]);
+        for i in 1..=self.max_rounds {
+            let start = Instant::now();
+            let (guess, remaining) = guesser.try_guess_remaining(&history[..])?;
+            let elapsed = start.elapsed();
             if guess == answer {
-                return Some(i);
+                record.turns.push(Turn { word: guess, mask: [Correctness::Correct; 5], remaining, elapsed });
+                record.outcome = if i <= self.win_rounds {
+                    Outcome::Won(i)
+                } else {
+                    Outcome::SolvedLate(i)
+                };
+                return Ok(record);
             }
 
-            assert!(self.dictionary.contains(&*guess));
+            self.check_word(&guess)?;
+            if self.hard_mode {
+                if let Err(violation) = Guess::check_hard_mode(&history, &guess) {
+                    return Err(Error::HardMode { guess, round: i, violation });
+                }
+            }
 
             let correctness = Correctness::compute(answer, &guess);
+            record.turns.push(Turn { word: guess.clone(), mask: correctness, remaining, elapsed });
             history.push(Guess {
                 word: Cow::Owned(guess),
                 mask: correctness,
             })
         }
-        None
+        Ok(record)
+    }
+
+    /// checks whether any dictionary word matches every guess of `history`. Returns `None` if one
+    /// does, otherwise the guesses that contradict each other and the nearest fix of their masks.
+    /// Every guessed word of `history` must be five letters long
+    pub fn find_contradiction(&self, history: &[Guess]) -> Option<Contradiction> {
+        let words: Vec<&str> = self.words().collect();
+        contradiction::find(&words, history)
+    }
+
+    /// returns an error if `word` is not five letters long, or is not in the dictionary
+    fn check_word(&self, word: &str) -> Result<(), Error> {
+        if word.len() != 5 {
+            Err(Error::WrongLength(word.to_string()))
+        } else if !self.dictionary.contains(word) {
+            Err(Error::InvalidWord(word.to_string()))
+        } else {
+            Ok(())
+        }
     }
 }
 
 impl Default for Wordle {
+    /// creates a game that accepts the words of the embedded dictionary as guesses
     fn default() -> Self {
-        Self::new()
+        Self::new(WordList::dictionary())
     }
 }
 
+/// The record of a game of wordle, every guess that was made and how it was made
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameRecord {
+    /// the answer of the game
+    pub answer: &'static str,
+    /// every guess of the game in order, the last one is the answer if the game was solved
+    pub turns: Vec<Turn>,
+    /// how the game ended
+    pub outcome: Outcome,
+}
+
+impl GameRecord {
+    /// returns `true` if the answer was guessed
+    pub fn solved(&self) -> bool {
+        self.outcome != Outcome::Unsolved
+    }
+
+    /// returns `Some(round_number)` of the round the answer was guessed in, else `None` if it
+    /// was not guessed
+    pub fn score(&self) -> Option<usize> {
+        match self.outcome {
+            Outcome::Won(rounds) | Outcome::SolvedLate(rounds) => Some(rounds),
+            Outcome::Unsolved => None,
+        }
+    }
+}
+
+/// How a game of wordle ended
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Outcome {
+    /// the answer was guessed within the win rounds, six by the official rules
+    Won(usize),
+    /// the answer was guessed, but it took more than the win rounds
+    SolvedLate(usize),
+    /// the answer was not guessed within the maximum number of rounds
+    Unsolved,
+}
+
+/// A guess made in a game of wordle
+#[derive(Debug, Clone, PartialEq)]
+pub struct Turn {
+    /// the guessed word
+    pub word: String,
+    /// the correctness mask of the guessed word compared against the answer
+    pub mask: [Correctness; 5],
+    /// the number of words that could still be the answer when the guess was made, if the
+    /// guesser keeps track of them
+    pub remaining: Option<usize>,
+    /// how long the guesser took to make the guess
+    pub elapsed: Duration,
+}
+
 /// Correctness holds the three possible 'states' for the characters of a word when compared
 /// against a wordle answer.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, PartialOrd, Ord)]
@@ -73,6 +234,18 @@ pub enum Correctness {
 }
 
 impl Correctness {
+    /// computes and returns the Correctness "mask" for each character of the given `guess`
+    /// when compared against the characters of the given `answer`, or an error if either
+    /// word is not five letters long
+    pub fn try_compute(answer: &str, guess: &str) -> Result<[Self; 5], Error> {
+        for word in [answer, guess] {
+            if word.len() != 5 {
+                return Err(Error::WrongLength(word.to_string()));
+            }
+        }
+        Ok(Self::compute(answer, guess))
+    }
+
     /// computes and returns the Correctness "mask" for each character of the given `guess`
     /// when compared against the characters of the given `answer`.
     fn compute(answer: &str, guess: &str) -> [Self; 5] {
@@ -144,6 +317,17 @@ impl Correctness {
             Ok(mask)
         }
     }
+
+    /// formats a mask the way `try_from_str` parses it, e.g. `ccwmw`
+    pub fn to_str(mask: &[Correctness; 5]) -> String {
+        mask.iter()
+            .map(|c| match c {
+                Correctness::Correct => 'c',
+                Correctness::Misplaced => 'm',
+                Correctness::Wrong => 'w',
+            })
+            .collect()
+    }
 }
 
 impl TryFrom<char> for Correctness {
@@ -180,10 +364,186 @@ impl Guess<'_> {
         // using Correctness::compute is a 18x runtime improvement over using old matches
         Correctness::compute(word, &self.word) == self.mask
     }
+
+    /// checks that `word` uses every hint revealed by this guess, following the NYT hard mode
+    /// rules: every green letter must be reused in the same position, and every yellow letter
+    /// must be reused somewhere
+    pub fn allows_hard_mode(&self, word: &str) -> Result<(), HardModeViolation> {
+        let guessed = self.word.as_bytes();
+        let word = word.as_bytes();
+
+        // greens are checked first, as NYT reports them before any yellows
+        for (position, c) in self.mask.iter().enumerate() {
+            if *c == Correctness::Correct && word.get(position) != Some(&guessed[position]) {
+                return Err(HardModeViolation::Green {
+                    position,
+                    letter: guessed[position] as char,
+                });
+            }
+        }
+        // a letter revealed more than once must be reused as many times
+        for (position, c) in self.mask.iter().enumerate() {
+            if *c != Correctness::Misplaced {
+                continue;
+            }
+            let letter = guessed[position];
+            let revealed = guessed
+                .iter()
+                .zip(&self.mask)
+                .filter(|&(&g, &c)| g == letter && c != Correctness::Wrong)
+                .count();
+            if word.iter().filter(|&&w| w == letter).count() < revealed {
+                return Err(HardModeViolation::Yellow {
+                    letter: letter as char,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// checks that `word` uses every hint revealed by all the guesses in `history`, see
+    /// `Guess::allows_hard_mode`
+    pub fn check_hard_mode(history: &[Guess], word: &str) -> Result<(), HardModeViolation> {
+        history
+            .iter()
+            .try_for_each(|guess| guess.allows_hard_mode(word))
+    }
+}
+
+/// The hard mode rule that a guess broke
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum HardModeViolation {
+    /// a green letter was not reused in the same position, `position` starts at zero
+    Green { position: usize, letter: char },
+    /// a yellow letter was not reused
+    Yellow { letter: char },
+}
+
+impl fmt::Display for HardModeViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HardModeViolation::Green { position, letter } => {
+                let ordinal = ["1st", "2nd", "3rd", "4th", "5th"][*position];
+                write!(f, "{} letter must be {}", ordinal, letter.to_ascii_uppercase())
+            }
+            HardModeViolation::Yellow { letter } => {
+                write!(f, "guess must contain {}", letter.to_ascii_uppercase())
+            }
+        }
+    }
+}
+
+/// The errors returned by the fallible methods of the library
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Error {
+    /// a word that is not in the dictionary
+    InvalidWord(String),
+    /// a word that is not five letters long
+    WrongLength(String),
+    /// no word of the dictionary matches every guess of the history, usually because a
+    /// correctness mask was mistyped
+    ContradictoryHistory,
+    /// the guesser has no words left to guess
+    NoCandidates,
+    /// a guess broke the hard mode rules, `round` starts at one
+    HardMode { guess: String, round: usize, violation: HardModeViolation },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidWord(word) => write!(f, "'{}' is not in the dictionary", word),
+            Error::WrongLength(word) => write!(f, "'{}' is not five letters long", word),
+            Error::ContradictoryHistory => {
+                write!(f, "no words match the guesses, perhaps a typo in a pattern")
+            }
+            Error::NoCandidates => write!(f, "there are no words left to guess"),
+            Error::HardMode { guess, round, violation } => {
+                write!(f, "guess '{}' in round {} breaks hard mode: {}", guess, round, violation)
+            }
+        }
+    }
 }
 
+impl std::error::Error for Error {}
+
 pub trait Guesser {
     fn guess(&mut self, history: &[Guess]) -> String;
+
+    /// returns the next guess like `guess`, or an error if the guesser can not make one.
+    /// The default implementation never fails
+    fn try_guess(&mut self, history: &[Guess]) -> Result<String, Error> {
+        Ok(self.guess(history))
+    }
+
+    /// returns the next guess like `try_guess`, along with the number of words that could
+    /// still be the answer, if the guesser keeps track of them. The default implementation
+    /// doesn't
+    fn try_guess_remaining(&mut self, history: &[Guess]) -> Result<(String, Option<usize>), Error> {
+        self.try_guess(history).map(|guess| (guess, None))
+    }
+}
+
+/// The details of a word suggested by a guessing algorithm, explaining why it was suggested
+#[derive(Debug, Clone, PartialEq)]
+pub struct Suggestion {
+    /// the suggested word
+    pub word: String,
+    /// the expected amount of information, in bits, gained by guessing `word`
+    pub bits: f64,
+    /// the score the algorithm ranked `word` by, higher is better. Depending on the
+    /// algorithm this is `bits`, `bits` weighted by `p_word`, the negated expected number of
+    /// guesses, the bits of two guesses or the negated size of the largest pattern bucket
+    pub goodness: f64,
+    /// the probability that `word` is the answer
+    pub p_word: f64,
+    /// the number of words that could still be the answer
+    pub remaining: usize,
+    /// the fraction of the total prior weight of all possible answers, usually the whole
+    /// dictionary, held by the remaining words. The weight is the occurrence count unless the
+    /// algorithm was given another `Prior`
+    pub remaining_mass: f64,
+}
+
+/// A companion to `Guesser` for algorithms that can explain and rank their guesses
+pub trait Suggester: Guesser {
+    /// returns up to `k` of the best next guesses, best first, along with the details of why
+    /// each was chosen. Words with equal scores are ranked in the order the algorithm scored them.
+    ///
+    /// An algorithm with a fixed opening word only returns that word when `history` is empty
+    fn rank(&mut self, history: &[Guess], k: usize) -> Vec<Suggestion>;
+
+    /// ranks the next guesses like `rank`, but returns an error if a guessed word of `history`
+    /// is not five letters long, or if no words are left to guess. When no words are left
+    /// after at least one guess, the `history` contradicts itself
+    fn try_rank(&mut self, history: &[Guess], k: usize) -> Result<Vec<Suggestion>, Error> {
+        if let Some(guess) = history.iter().find(|g| g.word.len() != 5) {
+            return Err(Error::WrongLength(guess.word.to_string()));
+        }
+        let ranked = self.rank(history, k);
+        if ranked.is_empty() && k > 0 {
+            return Err(if history.is_empty() {
+                Error::NoCandidates
+            } else {
+                Error::ContradictoryHistory
+            });
+        }
+        Ok(ranked)
+    }
+
+    /// returns the next best guess along with the details of why it was chosen
+    ///
+    /// # Panics
+    /// if `try_suggest` returns an error, e.g. no words match the `history`
+    fn suggest(&mut self, history: &[Guess]) -> Suggestion {
+        self.try_suggest(history)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// returns the next best guess like `suggest`, or an error, see `try_rank`
+    fn try_suggest(&mut self, history: &[Guess]) -> Result<Suggestion, Error> {
+        Ok(self.try_rank(history, 1)?.swap_remove(0))
+    }
 }
 
 impl Guesser for fn(history: &[Guess]) -> String {
@@ -275,18 +635,18 @@ mod tests {
     }
 
     mod game {
-        use crate::{Guess, Wordle};
+        use crate::{Guess, Outcome, Wordle};
 
         #[test]
         fn play_first_guess_is_correct() {
-            let w = Wordle::new();
+            let w = Wordle::default();
             let guesser = guesser!(|_history| { "right".to_string() });
             assert_eq!(w.play("right", guesser), Some(1));
         }
 
         #[test]
         fn play_second_guess_is_correct() {
-            let w = Wordle::new();
+            let w = Wordle::default();
             let guesser = guesser!(|history| {
                 if history.len() == 1 {
                     return "right".to_string();
@@ -299,7 +659,7 @@ mod tests {
 
         #[test]
         fn play_third_guess_is_correct() {
-            let w = Wordle::new();
+            let w = Wordle::default();
             let guesser = guesser!(|history| {
                 if history.len() == 2 {
                     return "right".to_string();
@@ -312,7 +672,7 @@ mod tests {
 
         #[test]
         fn play_fourth_guess_is_correct() {
-            let w = Wordle::new();
+            let w = Wordle::default();
             let guesser = guesser!(|history| {
                 if history.len() == 3 {
                     return "right".to_string();
@@ -325,7 +685,7 @@ mod tests {
 
         #[test]
         fn play_fifth_guess_is_correct() {
-            let w = Wordle::new();
+            let w = Wordle::default();
             let guesser = guesser!(|history| {
                 if history.len() == 4 {
                     return "right".to_string();
@@ -338,7 +698,7 @@ mod tests {
 
         #[test]
         fn play_sixth_guess_is_correct() {
-            let w = Wordle::new();
+            let w = Wordle::default();
             let guesser = guesser!(|history| {
                 if history.len() == 5 {
                     return "right".to_string();
@@ -351,11 +711,302 @@ mod tests {
 
         #[test]
         fn all_wrong_guesses_should_terminate() {
-            let w = Wordle::new();
+            let w = Wordle::default();
             let guesser = guesser!(|_history| { "wrong".to_string() });
 
             assert_eq!(w.play("right", guesser), None);
         }
+
+        #[test]
+        fn record_keeps_every_guess() {
+            let w = Wordle::default();
+            let guesser = guesser!(|history| {
+                if history.len() == 1 {
+                    return "right".to_string();
+                }
+                return "wrong".to_string();
+            });
+
+            let record = w.record("right", guesser);
+            assert_eq!(record.answer, "right");
+            assert!(record.solved());
+            assert_eq!(record.score(), Some(2));
+            let words: Vec<&str> = record.turns.iter().map(|t| t.word.as_str()).collect();
+            assert_eq!(words, ["wrong", "right"]);
+            assert_eq!(record.turns[0].mask, mask![W M W W M]);
+            assert_eq!(record.turns[1].mask, mask![C C C C C]);
+            assert!(record.turns.iter().all(|t| t.remaining.is_none()));
+        }
+
+        #[test]
+        fn outcome_depends_on_win_rounds() {
+            let guesser = || guesser!(|history| {
+                if history.len() == 6 {
+                    return "right".to_string();
+                }
+                return "wrong".to_string();
+            });
+            assert_eq!(Wordle::default().record("right", guesser()).outcome, Outcome::SolvedLate(7));
+            let w = Wordle::default().with_win_rounds(7);
+            assert_eq!(w.record("right", guesser()).outcome, Outcome::Won(7));
+        }
+
+        #[test]
+        fn max_rounds_ends_the_game() {
+            let guesser = || guesser!(|history| {
+                if history.len() == 4 {
+                    return "right".to_string();
+                }
+                return "wrong".to_string();
+            });
+            let w = Wordle::default().with_max_rounds(4);
+            let record = w.record("right", guesser());
+            assert_eq!(record.outcome, Outcome::Unsolved);
+            assert_eq!(record.turns.len(), 4);
+            assert_eq!(w.play("right", guesser()), None);
+            assert_eq!(Wordle::default().with_max_rounds(5).play("right", guesser()), Some(5));
+        }
+
+        #[test]
+        fn record_counts_remaining_words_of_suggesters() {
+            let w = Wordle::default();
+            let record = w.record("cigar", crate::algorithms::Prune::default());
+            assert_eq!(record.turns[0].remaining, Some(12947));
+            let remaining: Vec<usize> = record.turns.iter().map(|t| t.remaining.unwrap()).collect();
+            assert!(remaining.windows(2).all(|r| r[0] > r[1]), "{:?}", remaining);
+            assert_eq!(record.score(), w.play("cigar", crate::algorithms::Prune::default()));
+        }
+    }
+
+    mod suggest {
+        use std::borrow::Cow;
+
+        use crate::{Guess, Guesser, Prior, Suggester, WordList};
+        use crate::algorithms::{GuessPool, Opener, Prune, Weight};
+
+        #[test]
+        fn opener_describes_the_whole_dictionary() {
+            let s = Prune::default().suggest(&[]);
+            assert_eq!(s.word, "tares");
+            assert_eq!(s.remaining, 12947);
+            assert!((s.remaining_mass - 1.0).abs() < 1e-9);
+            assert!(s.bits > 0.0);
+            assert!(s.p_word > 0.0);
+        }
+
+        #[test]
+        fn suggestion_is_the_guess() {
+            let history = [Guess {
+                word: Cow::Borrowed("tares"),
+                mask: mask![C W C W W],
+            }];
+            let mut guesser = Prune::default();
+            guesser.guess(&[]);
+            let s = guesser.suggest(&history);
+            assert!(s.remaining > 0);
+            assert!(history[0].matches(&s.word));
+            assert_eq!(s.goodness, s.p_word * s.bits);
+
+            let mut guesser = Prune::default();
+            guesser.guess(&[]);
+            assert_eq!(guesser.guess(&history), s.word);
+        }
+
+        #[test]
+        fn opener_is_configurable() {
+            let s = Prune::default()
+                .with_opener(Opener::Word("salet".to_string()))
+                .suggest(&[]);
+            assert_eq!(s.word, "salet");
+            assert_eq!(s.remaining, 12947);
+        }
+
+        #[test]
+        fn probe_word_splits_the_atch_trap() {
+            // leaves batch, catch, gatch, hatch, latch, natch, patch, ratch and watch
+            let history = [Guess {
+                word: Cow::Borrowed("match"),
+                mask: mask![W C C C C],
+            }];
+            let mut remaining_only = Prune::default();
+            remaining_only.guess(&[]);
+            let candidate = remaining_only.suggest(&history);
+            assert!(candidate.word.ends_with("atch"));
+
+            let mut guesser = Prune::default().with_pool(GuessPool::Dictionary);
+            guesser.guess(&[]);
+            let probe = guesser.suggest(&history);
+            assert!(!history[0].matches(&probe.word), "{} is a probe", probe.word);
+            assert_eq!(probe.p_word, 0.0);
+            assert!(probe.bits > candidate.bits);
+        }
+
+        #[test]
+        fn hard_pool_only_guesses_words_that_use_every_hint() {
+            let history = [Guess {
+                word: Cow::Borrowed("match"),
+                mask: mask![W C C C C],
+            }];
+            let mut guesser = Prune::default().with_pool(GuessPool::Hard);
+            guesser.guess(&[]);
+            for s in guesser.rank(&history, 10) {
+                assert!(Guess::check_hard_mode(&history, &s.word).is_ok(), "{} breaks hard mode", s.word);
+            }
+        }
+
+        #[test]
+        fn answers_restrict_the_candidates() {
+            let answers = WordList::answers();
+            let mut guesser = Prune::default().with_answers(answers);
+            let s = guesser.suggest(&[]);
+            assert_eq!(s.remaining, answers.len());
+            assert!((s.remaining_mass - 1.0).abs() < 1e-9);
+
+            let history = [Guess {
+                word: Cow::Borrowed("tares"),
+                mask: mask![W M W W W],
+            }];
+            for s in guesser.rank(&history, 5) {
+                assert!(answers.contains(&s.word), "{} is an answer", s.word);
+                assert!(s.remaining < answers.len());
+            }
+        }
+
+        #[test]
+        fn prior_weighs_the_candidates() {
+            let words = WordList::dictionary();
+            let s = Prune::default().with_prior(Prior::Uniform).suggest(&[]);
+            assert!((s.p_word - 1.0 / words.len() as f64).abs() < 1e-12);
+            assert!((s.remaining_mass - 1.0).abs() < 1e-9);
+
+            // the count prior is the default
+            let count = Prune::default().with_prior(Prior::Count).suggest(&[]);
+            assert_eq!(count, Prune::default().suggest(&[]));
+
+            let history = [Guess {
+                word: Cow::Borrowed("tares"),
+                mask: mask![W M W W W],
+            }];
+            let mut prune = Prune::default().with_prior(Prior::SIGMOID);
+            let mut weight = Weight::default().with_prior(Prior::SIGMOID);
+            prune.suggest(&[]);
+            weight.suggest(&[]);
+            // prune may suggest another word, as it prunes patterns while scoring, but it weighs
+            // its suggestion like weight does
+            let p = prune.suggest(&history);
+            let w = weight.rank(&history, words.len()).into_iter().find(|w| w.word == p.word).unwrap();
+            assert!((p.p_word - w.p_word).abs() < 1e-9);
+            assert!(p.bits <= w.bits + 1e-9);
+            assert!(p.remaining_mass < 1.0);
+        }
+
+        #[test]
+        fn remaining_word_wins_ties_with_probes() {
+            // leaves only batch and hatch, every guess splits them at best into two patterns
+            let history = [
+                Guess { word: Cow::Borrowed("match"), mask: mask![W C C C C] },
+                Guess { word: Cow::Borrowed("growl"), mask: mask![W W W W W] },
+                Guess { word: Cow::Borrowed("pinky"), mask: mask![W W W W W] },
+                Guess { word: Cow::Borrowed("cough"), mask: mask![M W W W C] },
+            ];
+            let mut guesser = Prune::default().with_pool(GuessPool::Dictionary);
+            for turn in 0..history.len() {
+                guesser.guess(&history[..turn]);
+            }
+            let s = guesser.suggest(&history);
+            assert_eq!(s.remaining, 2);
+            assert!(history.iter().all(|g| g.matches(&s.word)), "{} is a remaining word", s.word);
+        }
+
+        #[test]
+        fn rank_is_best_first() {
+            let history = [Guess {
+                word: Cow::Borrowed("tares"),
+                mask: mask![C W C W W],
+            }];
+            let mut guesser = Prune::default();
+            assert_eq!(guesser.rank(&[], 5).len(), 1);
+            let ranked = guesser.rank(&history, 5);
+            assert_eq!(ranked.len(), 5);
+            assert!(ranked.windows(2).all(|w| w[0].goodness >= w[1].goodness));
+
+            let mut guesser = Prune::default();
+            guesser.guess(&[]);
+            assert_eq!(guesser.suggest(&history), ranked[0]);
+        }
+    }
+
+    mod hard_mode {
+        use std::borrow::Cow;
+
+        use crate::{Guess, HardModeViolation, Wordle};
+
+        fn guess(word: &'static str, mask: [crate::Correctness; 5]) -> Guess<'static> {
+            Guess { word: Cow::Borrowed(word), mask }
+        }
+
+        #[test]
+        fn reusing_hints_is_allowed() {
+            let history = [guess("tares", mask![C W W M W])];
+            assert_eq!(Guess::check_hard_mode(&history, "tepid"), Ok(()));
+            assert_eq!(Guess::check_hard_mode(&history, "their"), Ok(()));
+        }
+
+        #[test]
+        fn green_must_stay_in_place() {
+            let history = [guess("tares", mask![C W W M W])];
+            assert_eq!(
+                Guess::check_hard_mode(&history, "etude"),
+                Err(HardModeViolation::Green { position: 0, letter: 't' })
+            );
+            assert_eq!(
+                HardModeViolation::Green { position: 1, letter: 'a' }.to_string(),
+                "2nd letter must be A"
+            );
+        }
+
+        #[test]
+        fn yellow_must_be_reused() {
+            let history = [guess("tares", mask![C W W M W])];
+            assert_eq!(
+                Guess::check_hard_mode(&history, "tonic"),
+                Err(HardModeViolation::Yellow { letter: 'e' })
+            );
+            assert_eq!(HardModeViolation::Yellow { letter: 'e' }.to_string(), "guess must contain E");
+        }
+
+        #[test]
+        fn repeated_yellow_must_be_reused_as_often() {
+            let history = [guess("geese", mask![W M W W M])];
+            assert_eq!(
+                Guess::check_hard_mode(&history, "tepid"),
+                Err(HardModeViolation::Yellow { letter: 'e' })
+            );
+            assert_eq!(Guess::check_hard_mode(&history, "eerie"), Ok(()));
+        }
+
+        #[test]
+        fn every_guess_of_the_history_is_checked() {
+            let history = [guess("tares", mask![W W W M W]), guess("olden", mask![W C W M W])];
+            assert_eq!(Guess::check_hard_mode(&history, "blend"), Ok(()));
+            assert_eq!(
+                Guess::check_hard_mode(&history, "creep"),
+                Err(HardModeViolation::Green { position: 1, letter: 'l' })
+            );
+        }
+
+        #[test]
+        #[should_panic(expected = "breaks hard mode: 1st letter must be T")]
+        fn play_rejects_hard_mode_violations() {
+            let w = Wordle::default().with_hard_mode(true);
+            let guesser = guesser!(|history| {
+                if history.is_empty() {
+                    return "tares".to_string();
+                }
+                "etude".to_string()
+            });
+            w.play("tepid", guesser);
+        }
     }
 
     mod compute {
@@ -406,4 +1057,89 @@ mod tests {
             assert_eq!(Correctness::compute("abcde", "aacde"), mask!(C W C C C))
         }
     }
-}
\ No newline at end of file
+    mod errors {
+        use std::borrow::Cow;
+
+        use crate::{Correctness, Error, Guess, HardModeViolation, Suggester, Wordle};
+        use crate::algorithms::{Allocs, ExpectedTurns, GuessPool, Lookahead, Minimax, OnceInit, PreCalc, Prune,
+            Unoptimized, Vecrem, Weight};
+
+        #[test]
+        fn try_compute_rejects_wrong_lengths() {
+            assert_eq!(Correctness::try_compute("abcde", "abcd"), Err(Error::WrongLength("abcd".to_string())));
+            assert_eq!(Correctness::try_compute("abcdef", "abcde"), Err(Error::WrongLength("abcdef".to_string())));
+            assert_eq!(Correctness::try_compute("abcde", "aacde"), Ok(mask![C W C C C]));
+        }
+
+        #[test]
+        fn try_play_rejects_invalid_words() {
+            let w = Wordle::default();
+            let guesser = guesser!(|_history| { "tares".to_string() });
+            assert_eq!(w.try_play("zzzzz", guesser), Err(Error::InvalidWord("zzzzz".to_string())));
+
+            let guesser = guesser!(|_history| { "tare".to_string() });
+            assert_eq!(w.try_play("tepid", guesser), Err(Error::WrongLength("tare".to_string())));
+
+            let guesser = guesser!(|_history| { "tepid".to_string() });
+            assert_eq!(w.try_play("tepid", guesser), Ok(Some(1)));
+        }
+
+        #[test]
+        fn try_play_reports_hard_mode_violations() {
+            let w = Wordle::default().with_hard_mode(true);
+            let guesser = guesser!(|history| {
+                if history.is_empty() {
+                    return "tares".to_string();
+                }
+                "etude".to_string()
+            });
+            assert_eq!(w.try_play("tepid", guesser), Err(Error::HardMode {
+                guess: "etude".to_string(),
+                round: 2,
+                violation: HardModeViolation::Green { position: 0, letter: 't' },
+            }));
+        }
+
+        #[test]
+        fn contradictory_history_is_an_error() {
+            // every letter of tares is placed, so there is nowhere left for the yellow s
+            let history = [Guess { word: Cow::Borrowed("tares"), mask: mask![C C C C M] }];
+            let guessers: Vec<Box<dyn Suggester>> = vec![
+                Box::new(Unoptimized::default()),
+                Box::new(Allocs::default()),
+                Box::new(Vecrem::default()),
+                Box::new(OnceInit::default()),
+                Box::new(PreCalc::default()),
+                Box::new(Weight::default()),
+                Box::new(Prune::default()),
+                Box::new(ExpectedTurns::default()),
+                Box::new(Lookahead::default()),
+                Box::new(Minimax::default()),
+            ];
+            for mut guesser in guessers {
+                assert_eq!(guesser.try_suggest(&history), Err(Error::ContradictoryHistory));
+                assert_eq!(guesser.try_guess(&history), Err(Error::ContradictoryHistory));
+            }
+
+            // probe words are not suggested once no word could be the answer
+            for pool in [GuessPool::Dictionary, GuessPool::Hard] {
+                let guessers: Vec<Box<dyn Suggester>> = vec![
+                    Box::new(Prune::default().with_pool(pool)),
+                    Box::new(ExpectedTurns::default().with_pool(pool)),
+                    Box::new(Lookahead::default().with_pool(pool)),
+                    Box::new(Minimax::default().with_pool(pool)),
+                ];
+                for mut guesser in guessers {
+                    assert_eq!(guesser.try_suggest(&history), Err(Error::ContradictoryHistory), "{:?}", pool);
+                    assert_eq!(guesser.try_guess(&history), Err(Error::ContradictoryHistory), "{:?}", pool);
+                }
+            }
+        }
+
+        #[test]
+        fn mistyped_guess_is_an_error() {
+            let history = [Guess { word: Cow::Borrowed("tare"), mask: mask![W W W W W] }];
+            assert_eq!(Prune::default().try_rank(&history, 3), Err(Error::WrongLength("tare".to_string())));
+        }
+    }
+}
@@ -1,19 +1,84 @@
 use std::borrow::Cow;
 use std::io::Write;
+use std::path::PathBuf;
 use anyhow::anyhow;
 use clap::{ArgEnum, Parser};
-use wordle_solver::{Correctness, Guess, Guesser};
+use wordle_solver::{Correctness, Error, Guess, Prior, Suggester, Suggestion, WordList, Wordle};
+use wordle_solver::algorithms::{ExpectedTurns, GuessPool, Lookahead, Minimax, Opener, Prune, TurnsEstimator};
 
 #[derive(Parser, Debug)]
 #[clap(author, version, about, long_about = None)]
 struct Args {
     /// Name of the wordle guesser implementation to use
-    #[clap(short, long, arg_enum)]
+    #[clap(short, long, arg_enum, default_value_t = Implementation::Prune)]
     implementation: Implementation,
 
-    /// max Number of games to play
+    /// let the prune implementation guess any dictionary word, even one that can no longer be
+    /// the answer, when it splits the remaining words better (normal mode probe guesses)
+    #[clap(long)]
+    probes: bool,
+
+    /// only suggest words that follow the NYT hard mode rules, probe words that use every
+    /// revealed hint may be suggested
+    #[clap(long)]
+    hard: bool,
+
+    /// number of threads used to score candidate words, defaults to the number of CPU cores
     #[clap(short, long)]
-    max: Option<usize>,
+    threads: Option<usize>,
+
+    /// the first word to suggest, or 'compute' to score every word of the dictionary and suggest
+    /// the best one
+    #[clap(short, long, default_value = "tares")]
+    opener: Opener,
+
+    /// number of alternative guesses to show after the best guess
+    #[clap(short, long, default_value_t = 4)]
+    alternatives: usize,
+
+    /// a file of the words that may be guessed, one word per line followed by an optional
+    /// occurrence count, defaults to the embedded dictionary
+    #[clap(long, parse(from_os_str))]
+    dictionary: Option<PathBuf>,
+
+    /// a file of possible answers, one word per line, defaults to the embedded past answers.
+    /// Used with --restrict, and by the expected-turns implementation to learn from
+    #[clap(long, parse(from_os_str))]
+    answers: Option<PathBuf>,
+
+    /// let the prune, expected-turns, lookahead or minimax implementation only consider the
+    /// answers list as possible answers, while any dictionary word can still be guessed as a probe
+    #[clap(long)]
+    restrict: bool,
+
+    /// how likely each word is to be the answer, for the weight and prune implementations:
+    /// 'count' (occurrence count), 'uniform', 'sigmoid' (over frequency rank) or
+    /// 'sigmoid:<midpoint>:<width>'
+    #[clap(long, default_value = "count")]
+    prior: Prior,
+
+    /// a prior file to read the prior from instead of --prior, such as the one written by
+    /// the fit_prior program
+    #[clap(long, parse(from_os_str), conflicts_with = "prior")]
+    prior_file: Option<PathBuf>,
+
+    /// the number of best single-step guesses the lookahead implementation looks two guesses
+    /// ahead from
+    #[clap(long, default_value_t = Lookahead::DEFAULT_BEAM)]
+    beam: usize,
+
+    /// learn the expected-turns estimator from games of prune played with the pool, --restrict
+    /// and --hard settings of this run, instead of using the estimator learned from the default
+    /// games. It is always learned again when --prior, --prior-file, --dictionary or --answers
+    /// is given
+    #[clap(long)]
+    learn: bool,
+
+    /// path of the pattern table cache file, defaults to a file in the system temp directory
+    /// named after the dictionary. Only the prune, expected-turns, lookahead and minimax
+    /// implementations load the table
+    #[clap(long, parse(from_os_str))]
+    cache: Option<PathBuf>,
 }
 
 /// various Wordle guesser implementations
@@ -25,35 +90,316 @@ enum Implementation {
     Once,
     Precalc,
     Weight,
-    Prune
+    Prune,
+    ExpectedTurns,
+    Lookahead,
+    Minimax,
+}
+
+impl Implementation {
+    /// returns `true` if the implementation can restrict the possible answers, see --restrict
+    fn restricts(self) -> bool {
+        matches!(self, Implementation::Prune | Implementation::ExpectedTurns | Implementation::Lookahead | Implementation::Minimax)
+    }
+
+    /// returns `true` if the implementation reads the pattern table, see --cache
+    fn uses_table(self) -> bool {
+        matches!(self, Implementation::Prune | Implementation::ExpectedTurns | Implementation::Lookahead | Implementation::Minimax)
+    }
 }
 
 fn main() -> Result<(), anyhow::Error> {
-    // use the Prune algorithm as it is the fastest so far
-    let mut guesser = wordle_solver::algorithms::Prune::new();
+    let args = Args::parse();
+
+    let dictionary = WordList::load_or(args.dictionary.as_deref(), WordList::dictionary())?;
+    let answers = WordList::load_or(args.answers.as_deref(), WordList::answers())?;
+    // the older implementations never read the table, so they don't pay for building it
+    if args.implementation.uses_table() {
+        let cache = args.cache.unwrap_or_else(|| dictionary.default_cache_path());
+        eprintln!("loading pattern table from {}", cache.display());
+        dictionary.load_table(&cache)?;
+    }
+    let threads = args.threads.unwrap_or_else(|| {
+        std::thread::available_parallelism().map_or(1, |n| n.get())
+    });
+    let pool = if args.hard {
+        GuessPool::Hard
+    } else if args.probes {
+        GuessPool::Dictionary
+    } else {
+        GuessPool::Remaining
+    };
+
+    let alternatives = args.alternatives;
+    let opener = args.opener;
+    let prior = match &args.prior_file {
+        Some(path) => Prior::load(path)?,
+        None => args.prior,
+    };
+    let restrict = args.restrict;
+    if restrict && !args.implementation.restricts() {
+        return Err(anyhow!("--restrict is only supported by the prune, expected-turns, lookahead and minimax implementations"));
+    }
+    // the words that could be the answer, a guess that leaves none of them is ignored
+    let possible = if restrict { answers } else { dictionary };
+    match args.implementation {
+        Implementation::Unoptimized => run(|| wordle_solver::algorithms::Unoptimized::new(dictionary).with_opener(opener.clone()), possible, alternatives),
+        Implementation::Allocs => run(|| wordle_solver::algorithms::Allocs::new(dictionary).with_opener(opener.clone()), possible, alternatives),
+        Implementation::Vecrem => run(|| wordle_solver::algorithms::Vecrem::new(dictionary).with_opener(opener.clone()), possible, alternatives),
+        Implementation::Once => run(|| wordle_solver::algorithms::OnceInit::new(dictionary).with_opener(opener.clone()), possible, alternatives),
+        Implementation::Precalc => run(|| wordle_solver::algorithms::PreCalc::new(dictionary).with_opener(opener.clone()), possible, alternatives),
+        Implementation::Weight => run(|| wordle_solver::algorithms::Weight::new(dictionary).with_opener(opener.clone()).with_prior(prior), possible, alternatives),
+        // use the Prune algorithm by default as it is the fastest so far
+        Implementation::Prune => {
+            let prune = || {
+                let prune = Prune::new(dictionary)
+                    .with_opener(opener.clone())
+                    .with_pool(pool)
+                    .with_threads(threads)
+                    .with_prior(prior);
+                if restrict {
+                    prune.with_answers(answers)
+                } else {
+                    prune
+                }
+            };
+            run(prune, possible, alternatives)
+        }
+        Implementation::ExpectedTurns => {
+            // the default estimator was learned from games of the default prune, on the embedded
+            // dictionary and answers
+            let learn = args.learn
+                || args.dictionary.is_some()
+                || args.answers.is_some()
+                || args.prior_file.is_some()
+                || prior != Prior::Count;
+            let estimator = if learn {
+                eprintln!("learning how many guesses games take from {} games of prune", answers.len());
+                let prune = || {
+                    let prune = Prune::new(dictionary)
+                        .with_pool(pool)
+                        .with_threads(threads)
+                        .with_prior(prior);
+                    if restrict {
+                        prune.with_answers(answers)
+                    } else {
+                        prune
+                    }
+                };
+                TurnsEstimator::simulate(&Wordle::new(dictionary).with_hard_mode(args.hard), answers, prune)?
+            } else {
+                TurnsEstimator::default()
+            };
+            let expected_turns = || {
+                let expected_turns = ExpectedTurns::new(dictionary)
+                    .with_opener(opener.clone())
+                    .with_pool(pool)
+                    .with_threads(threads)
+                    .with_prior(prior)
+                    .with_estimator(estimator.clone());
+                if restrict {
+                    expected_turns.with_answers(answers)
+                } else {
+                    expected_turns
+                }
+            };
+            run(expected_turns, possible, alternatives)
+        }
+        Implementation::Lookahead => {
+            let lookahead = || {
+                let lookahead = Lookahead::new(dictionary)
+                    .with_opener(opener.clone())
+                    .with_pool(pool)
+                    .with_threads(threads)
+                    .with_prior(prior)
+                    .with_beam(args.beam);
+                if restrict {
+                    lookahead.with_answers(answers)
+                } else {
+                    lookahead
+                }
+            };
+            run(lookahead, possible, alternatives)
+        }
+        Implementation::Minimax => {
+            let minimax = || {
+                let minimax = Minimax::new(dictionary)
+                    .with_opener(opener.clone())
+                    .with_pool(pool)
+                    .with_threads(threads)
+                    .with_prior(prior);
+                if restrict {
+                    minimax.with_answers(answers)
+                } else {
+                    minimax
+                }
+            };
+            run(minimax, possible, alternatives)
+        }
+    }
+}
+
+/// reads guesses and their correctness masks from stdin, printing the next best guess of a
+/// guesser created by `maker`, and why it was suggested, after each one. Up to `alternatives`
+/// runner-up guesses are printed after the best guess.
+/// A mistyped line is reported and entered again. A guess whose mask leaves none of the
+/// `answers` as a possible answer is ignored, and the mask that was most likely meant is printed
+/// instead
+fn run<G: Suggester>(maker: impl Fn() -> G, answers: &'static WordList, alternatives: usize) -> Result<(), anyhow::Error> {
+    let wordle = Wordle::new(answers);
+    let mut guesser = maker();
     let mut guess_history: Vec<Guess> = Vec::new();
 
+    println!("start with... {}", guesser.suggest(&guess_history).word);
     println!("Enter a guess and its resulting correctness mask separated by a space then press ENTER, example:'tares ccwmm'");
-    for turn in 1.. {
+    loop {
+        let turn = guess_history.len() + 1;
         print!("Turn {} Guess and Pattern:", turn);
         std::io::stdout().flush()?;
 
         let mut input = String::new();
-        std::io::stdin()
-            .read_line(&mut input)?;
-        let (word, mask) = input
-            .trim_end()
-            .split_once(' ')
-            .ok_or_else(|| anyhow!("guess and mask must be separated by one space"))?;
-
-        let correctness = Correctness::try_from_str(mask)?;
-        let guess = Guess {
-            word: Cow::Owned(word.to_string()),
-            mask: correctness,
+        if std::io::stdin().read_line(&mut input)? == 0 {
+            // end of input
+            println!();
+            return Ok(());
+        }
+        // a mistyped guess or mask is reported, and the turn is entered again
+        let guess = match parse_guess(&input) {
+            Ok(guess) => guess,
+            Err(e) => {
+                println!("{}", e);
+                continue;
+            }
         };
-        guess_history.push(guess);
-        let best_word = guesser.guess(&guess_history);
-        println!("try this guess... {}", &best_word);
+        let ranked = match add_guess(&maker, &mut guesser, &wordle, &mut guess_history, guess, alternatives + 1) {
+            Ok(ranked) => ranked,
+            Err(message) => {
+                println!("{}", message);
+                continue;
+            }
+        };
+        let (best, others) = ranked
+            .split_first()
+            .ok_or(Error::ContradictoryHistory)?;
+        println!("try this guess... {}", &best.word);
+        println!(
+            "  {:.2} bits of information, {:.1}% chance of being the answer, {} words ({:.2}% of the dictionary) remaining",
+            best.bits,
+            best.p_word * 100.0,
+            best.remaining,
+            best.remaining_mass * 100.0
+        );
+        if !others.is_empty() {
+            let others: Vec<String> = others
+                .iter()
+                .map(|s| format!("{} ({:.3})", s.word, s.goodness))
+                .collect();
+            println!("  alternatives: {}", others.join(", "));
+        }
+    }
+}
+
+/// parses a line of input holding a guess and its correctness mask separated by one space,
+/// e.g. `tares ccwmm`
+fn parse_guess(input: &str) -> Result<Guess<'static>, anyhow::Error> {
+    let (word, mask) = input
+        .trim_end()
+        .split_once(' ')
+        .ok_or_else(|| anyhow!("guess and mask must be separated by one space"))?;
+
+    if word.len() != 5 {
+        return Err(Error::WrongLength(word.to_string()).into());
+    }
+    let correctness = Correctness::try_from_str(mask)?;
+    Ok(Guess {
+        word: Cow::Owned(word.to_string()),
+        mask: correctness,
+    })
+}
+
+/// adds `guess` to the `history` and ranks up to `k` of the next guesses of `guesser`.
+/// A guess that leaves none of the words of `wordle` as the answer is removed from the history
+/// again, and the returned error describes the contradiction. If the guesser fails to rank its
+/// guesses anyway, the guess is removed too, and the guesser is replaced by a new one from
+/// `maker` that is given the remaining history again, since it may have pruned its words with
+/// the removed guess
+fn add_guess<G: Suggester>(
+    maker: &impl Fn() -> G,
+    guesser: &mut G,
+    wordle: &Wordle,
+    history: &mut Vec<Guess<'static>>,
+    guess: Guess<'static>,
+    k: usize,
+) -> Result<Vec<Suggestion>, String> {
+    history.push(guess);
+    if let Some(contradiction) = wordle.find_contradiction(history) {
+        history.pop();
+        return Err(format!("{}\n  the guess was ignored, no words match it along with the previous guesses", contradiction));
+    }
+    match guesser.try_rank(history, k) {
+        Ok(ranked) => Ok(ranked),
+        Err(e) => {
+            history.pop();
+            *guesser = maker();
+            for turn in 0..=history.len() {
+                // every turn was ranked before, so it can be ranked again
+                let _ = guesser.try_rank(&history[..turn], 1);
+            }
+            Err(format!("{}\n  the guess was ignored", e))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+    use wordle_solver::{Correctness, Guess, Suggester, WordList, Wordle};
+    use wordle_solver::algorithms::Prune;
+    use super::{add_guess, parse_guess};
+
+    fn guess(word: &'static str, mask: &str) -> Guess<'static> {
+        Guess { word: Cow::Borrowed(word), mask: Correctness::try_from_str(mask).unwrap() }
+    }
+
+    /// plays `tares`, then a guess that only a word outside of the answers fits, then a valid
+    /// guess, checking the words of `wordle` for contradictions
+    fn restricted_session(wordle: &Wordle) {
+        let maker = || Prune::new(WordList::dictionary()).with_answers(WordList::answers());
+        let mut guesser = maker();
+        guesser.suggest(&[]);
+        let mut history = Vec::new();
+
+        // aahed is not an answer, but fits tares wcwcw like the answer panel
+        assert!(add_guess(&maker, &mut guesser, wordle, &mut history, guess("tares", "wcwcw"), 1).is_ok());
+        assert!(add_guess(&maker, &mut guesser, wordle, &mut history, guess("aahed", "ccccc"), 1).is_err());
+        assert_eq!(history.len(), 1);
+
+        // the session goes on as if the ignored guess was never made
+        let ranked = add_guess(&maker, &mut guesser, wordle, &mut history, guess("panel", "ccccc"), 1).unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(ranked[0].word, "panel");
+    }
+
+    #[test]
+    fn restricted_session_ignores_a_guess_that_leaves_no_answer() {
+        restricted_session(&Wordle::new(WordList::answers()));
+    }
+
+    #[test]
+    fn guesser_is_recovered_when_it_can_not_rank() {
+        // the whole dictionary does not catch the contradiction, but the guesser still fails
+        restricted_session(&Wordle::new(WordList::dictionary()));
+    }
+
+    #[test]
+    fn mistyped_input_is_an_error() {
+        let guess = parse_guess("tares ccwmm\n").unwrap();
+        assert_eq!(guess.word, "tares");
+        assert_eq!(guess.mask, Correctness::try_from_str("ccwmm").unwrap());
+
+        assert!(parse_guess("tares\n").is_err());
+        assert!(parse_guess("tare ccwmm\n").is_err());
+        assert!(parse_guess("tares wcwcx\n").is_err());
+        assert!(parse_guess("tares ccwm\n").is_err());
     }
-    Ok(())
 }
@@ -0,0 +1,172 @@
+//! The parser of word list files, such as `dictionary.txt` and `answers.txt`.
+//!
+//! Every line of a word list holds a five letter, lowercase ASCII word, optionally followed by
+//! whitespace and the word's occurrence count. A word without a count gets `DEFAULT_COUNT`.
+//! Blank lines are skipped. Every line is validated, and all problems are reported together along
+//! with their line numbers, so a hand-edited list can be fixed in one go.
+//!
+use std::collections::HashMap;
+use std::fmt;
+use crate::pattern::WORD_LEN;
+
+/// the occurrence count of a word listed without one
+pub const DEFAULT_COUNT: usize = 1;
+
+/// The problems of a word list, and the name of the file they were found in
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ParseError {
+    /// the file, or other source, that was parsed
+    pub file: String,
+    /// every problem found, in line order
+    pub lines: Vec<LineError>,
+}
+
+/// A problem with a single line of a word list
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct LineError {
+    /// the line number, starting at one
+    pub line: usize,
+    /// what is wrong with the line
+    pub problem: Problem,
+}
+
+/// The ways a line of a word list can be invalid
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Problem {
+    /// the word is not five letters long
+    WrongLength(String),
+    /// the word has a character that is not a lowercase ASCII letter
+    NotLowercase(String),
+    /// the occurrence count is not a number
+    InvalidCount(String),
+    /// there is more text after the occurrence count
+    TrailingText(String),
+    /// the word was already listed on an earlier line
+    Duplicate { word: String, first_line: usize },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} has {} invalid line(s)", self.file, self.lines.len())?;
+        for error in &self.lines {
+            write!(f, "\n  {}:{}: {}", self.file, error.line, error.problem)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Problem::WrongLength(word) => write!(f, "'{}' is not {} letters long", word, WORD_LEN),
+            Problem::NotLowercase(word) => {
+                write!(f, "'{}' must only have lowercase letters a to z", word)
+            }
+            Problem::InvalidCount(count) => {
+                write!(f, "occurrence count '{}' is not a whole number", count)
+            }
+            Problem::TrailingText(text) => {
+                write!(f, "unexpected '{}' after the occurrence count", text)
+            }
+            Problem::Duplicate { word, first_line } => {
+                write!(f, "'{}' was already listed on line {}", word, first_line)
+            }
+        }
+    }
+}
+
+/// parses the word list `text` that was read from `file`, returning every `(word, count)` in
+/// order, or every problem found
+pub fn parse(file: &str, text: &'static str) -> Result<Vec<(&'static str, usize)>, ParseError> {
+    let mut entries = Vec::new();
+    let mut errors = Vec::new();
+    // maps a word to the line it was first listed on
+    let mut seen: HashMap<&str, usize> = HashMap::new();
+
+    for (i, line) in text.lines().enumerate() {
+        let line_number = i + 1;
+        let mut fields = line.split_whitespace();
+        let Some(word) = fields.next() else {
+            continue;
+        };
+        match parse_line(word, fields.next(), fields.collect::<Vec<_>>().join(" ")) {
+            Ok(count) => {
+                if let Some(&first_line) = seen.get(word) {
+                    errors.push(LineError {
+                        line: line_number,
+                        problem: Problem::Duplicate { word: word.to_string(), first_line },
+                    });
+                } else {
+                    seen.insert(word, line_number);
+                    entries.push((word, count));
+                }
+            }
+            Err(problem) => errors.push(LineError { line: line_number, problem }),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(entries)
+    } else {
+        Err(ParseError { file: file.to_string(), lines: errors })
+    }
+}
+
+/// validates the fields of a line, returning the occurrence count of `word`
+fn parse_line(word: &str, count: Option<&str>, rest: String) -> Result<usize, Problem> {
+    if !word.bytes().all(|b| b.is_ascii_lowercase()) {
+        return Err(Problem::NotLowercase(word.to_string()));
+    }
+    if word.len() != WORD_LEN {
+        return Err(Problem::WrongLength(word.to_string()));
+    }
+    let count = match count {
+        Some(count) => count
+            .parse()
+            .map_err(|_| Problem::InvalidCount(count.to_string()))?,
+        None => DEFAULT_COUNT,
+    };
+    if !rest.is_empty() {
+        return Err(Problem::TrailingText(rest));
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, LineError, Problem};
+
+    #[test]
+    fn counts_default_to_one() {
+        let entries = parse("words.txt", "tares 10\nbrink\n\n  speed\t3\r\n").unwrap();
+        assert_eq!(entries, vec![("tares", 10), ("brink", 1), ("speed", 3)]);
+    }
+
+    #[test]
+    fn every_problem_is_reported_with_its_line() {
+        let text = "tares 10\ntare 3\nTares\nbrink ten\nspeed 3 4\nbrink\ntares 2\n";
+        let error = parse("words.txt", text).unwrap_err();
+        assert_eq!(error.file, "words.txt");
+        assert_eq!(error.lines, vec![
+            LineError { line: 2, problem: Problem::WrongLength("tare".to_string()) },
+            LineError { line: 3, problem: Problem::NotLowercase("Tares".to_string()) },
+            LineError { line: 4, problem: Problem::InvalidCount("ten".to_string()) },
+            LineError { line: 5, problem: Problem::TrailingText("4".to_string()) },
+            LineError {
+                line: 7,
+                problem: Problem::Duplicate { word: "tares".to_string(), first_line: 1 },
+            },
+        ]);
+    }
+
+    #[test]
+    fn errors_name_the_file_and_line() {
+        let error = parse("words.txt", "tares\nbrink -1\n").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "words.txt has 1 invalid line(s)\n  words.txt:2: occurrence count '-1' is not a whole number"
+        );
+    }
+}
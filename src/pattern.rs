@@ -0,0 +1,423 @@
+//! Compact representations of wordle correctness patterns.
+//!
+//! A correctness "mask" of five `Correctness` values can be losslessly packed into a single
+//! base-3 number in the range `0..243`, a `PatternId`. Because a `PatternId` fits into a byte, the
+//! pattern produced by every (guess, answer) pair of the dictionary can be pre-computed into a
+//! dense `PatternTable`, turning the inner loop of the guessing algorithms into a table lookup.
+//!
+//! Building the table for the full dictionary takes a few seconds, so it can be saved to a cache
+//! file and memory-mapped on later runs. The cache file starts with a fixed size header:
+//!
+//! | bytes  | contents                                               |
+//! |--------|--------------------------------------------------------|
+//! | 0..8   | magic bytes `WORDLPAT`                                 |
+//! | 8..12  | format version, little endian u32                      |
+//! | 12..16 | word length, little endian u32                         |
+//! | 16..24 | number of words, little endian u64                     |
+//! | 24..32 | FNV-1a hash of the words the table was built from, u64 |
+//!
+//! followed by the `words^2` pattern ids of the table. A cache file whose header does not match
+//! the current dictionary is ignored and rebuilt.
+//!
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use memmap2::Mmap;
+use crate::Correctness;
+
+/// the number of letters in a wordle word
+pub const WORD_LEN: usize = 5;
+
+/// A correctness pattern packed into a base-3 number.
+///
+/// The first character of the mask is the most significant digit, and the digits are
+/// `Correct = 0`, `Misplaced = 1` and `Wrong = 2`. This means the id of a mask is the same as its
+/// position in the iterator returned by `Correctness::patterns()`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub struct PatternId(u8);
+
+impl PatternId {
+    /// the total number of distinct patterns, 3^5
+    pub const COUNT: usize = 243;
+
+    /// the pattern of a guess that is the answer, all greens
+    pub const CORRECT: PatternId = PatternId(0);
+
+    /// computes the `PatternId` of the correctness mask of `guess` when compared against
+    /// `answer`.
+    ///
+    /// This produces the same pattern as `Correctness::compute`, but it uses letter counts instead
+    /// of repeated scans of the answer, so it is cheap enough to call for every pair of words in
+    /// the dictionary.
+    pub fn compute(answer: &str, guess: &str) -> Self {
+        let answer = answer.as_bytes();
+        let guess = guess.as_bytes();
+        debug_assert_eq!(answer.len(), WORD_LEN);
+        debug_assert_eq!(guess.len(), WORD_LEN);
+
+        // digits of the pattern, start with everything marked Wrong
+        let mut digits = [2u8; WORD_LEN];
+        // counts of the answer letters that are not already matched by a green
+        let mut unmatched = [0u8; 256];
+
+        // mark green chars
+        for i in 0..WORD_LEN {
+            if answer[i] == guess[i] {
+                digits[i] = 0;
+            } else {
+                unmatched[answer[i] as usize] += 1;
+            }
+        }
+        // mark yellow chars, from left to right, while there are unmatched letters left
+        for i in 0..WORD_LEN {
+            if digits[i] == 0 {
+                continue;
+            }
+            let count = &mut unmatched[guess[i] as usize];
+            if *count > 0 {
+                *count -= 1;
+                digits[i] = 1;
+            }
+        }
+
+        Self(digits.iter().fold(0, |id, &d| id * 3 + d))
+    }
+
+    /// returns the raw value of this id, in the range `0..243`
+    pub fn get(self) -> u8 {
+        self.0
+    }
+
+    /// returns this id as an index into a 243 element array
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+
+    /// returns the `PatternId` for the given `value`, or `None` if `value` is not less than 243
+    pub fn from_u8(value: u8) -> Option<Self> {
+        if (value as usize) < Self::COUNT {
+            Some(Self(value))
+        } else {
+            None
+        }
+    }
+
+    /// returns an iterator over every `PatternId`, in the same order as `Correctness::patterns()`
+    pub fn all() -> impl Iterator<Item=Self> {
+        (0..Self::COUNT as u8).map(Self)
+    }
+}
+
+impl From<[Correctness; 5]> for PatternId {
+    fn from(mask: [Correctness; 5]) -> Self {
+        Self(mask.iter().fold(0, |id, c| {
+            id * 3 + match c {
+                Correctness::Correct => 0,
+                Correctness::Misplaced => 1,
+                Correctness::Wrong => 2,
+            }
+        }))
+    }
+}
+
+impl From<PatternId> for [Correctness; 5] {
+    fn from(id: PatternId) -> Self {
+        let mut mask = [Correctness::Wrong; 5];
+        let mut value = id.0;
+        for c in mask.iter_mut().rev() {
+            *c = match value % 3 {
+                0 => Correctness::Correct,
+                1 => Correctness::Misplaced,
+                _ => Correctness::Wrong,
+            };
+            value /= 3;
+        }
+        mask
+    }
+}
+
+/// magic bytes at the start of every pattern table cache file
+const MAGIC: &[u8; 8] = b"WORDLPAT";
+/// version of the cache file format, bump this if the layout of the file changes
+const VERSION: u32 = 1;
+/// size of the cache file header in bytes
+const HEADER_LEN: usize = 32;
+
+/// A dense, pre-computed table holding the `PatternId` of every (guess, answer) pair of a list of
+/// words.
+///
+/// The table is indexed by the position of the words in the list it was built from, so
+/// `table.get(g, a)` is the pattern wordle would show when guessing `words[g]` and the answer
+/// is `words[a]`. It needs `words.len()^2` bytes, about 168MB for the full dictionary.
+pub struct PatternTable {
+    /// number of words the table was built from
+    len: usize,
+    /// hash of the words the table was built from, see `words_hash`
+    hash: u64,
+    /// row major `len * len` pattern ids, one row per guess
+    data: Storage,
+}
+
+/// The bytes of a `PatternTable`, either computed in memory or mapped from a cache file
+enum Storage {
+    Owned(Vec<u8>),
+    /// a mapped cache file, the table data starts after the header
+    Mapped(Mmap),
+}
+
+impl Deref for Storage {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            Storage::Owned(data) => data,
+            Storage::Mapped(map) => &map[HEADER_LEN..],
+        }
+    }
+}
+
+impl PatternTable {
+    /// computes the pattern of every pair of `words`.
+    /// The rows of the table are split across all available CPU cores.
+    pub fn new(words: &[&str]) -> Self {
+        let len = words.len();
+        let mut data = vec![0u8; len * len];
+        let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+        let rows_per_thread = len.div_ceil(threads).max(1);
+
+        std::thread::scope(|s| {
+            for (chunk, rows) in data.chunks_mut(rows_per_thread * len.max(1)).enumerate() {
+                s.spawn(move || {
+                    for (r, row) in rows.chunks_mut(len).enumerate() {
+                        let guess = words[chunk * rows_per_thread + r];
+                        for (id, answer) in row.iter_mut().zip(words) {
+                            *id = PatternId::compute(answer, guess).0;
+                        }
+                    }
+                });
+            }
+        });
+
+        Self {
+            len,
+            hash: words_hash(words),
+            data: Storage::Owned(data),
+        }
+    }
+
+    /// loads a table of `words` from the cache file at `path` if it is up-to-date, else builds
+    /// the table and (re)writes the cache file.
+    pub fn load_or_build(path: &Path, words: &[&str]) -> io::Result<Self> {
+        if let Some(table) = Self::load(path, words)? {
+            return Ok(table);
+        }
+        let table = Self::new(words);
+        table.save(path)?;
+        Ok(table)
+    }
+
+    /// memory-maps a previously saved table from the cache file at `path`.
+    /// Returns `Ok(None)` if the file does not exist, or if it was not built from `words`
+    pub fn load(path: &Path, words: &[&str]) -> io::Result<Option<Self>> {
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        // Safety: the cache file is only ever replaced by renaming a new file over it, so the
+        // mapped file is not modified while it is in use
+        let map = unsafe { Mmap::map(&file)? };
+
+        let len = words.len();
+        let expected = header(len, words_hash(words));
+        if map.len() != HEADER_LEN + len * len || map[..HEADER_LEN] != expected {
+            return Ok(None);
+        }
+        Ok(Some(Self {
+            len,
+            hash: words_hash(words),
+            data: Storage::Mapped(map),
+        }))
+    }
+
+    /// writes this table to a cache file at `path`.
+    /// The file is written to a temporary file first and then renamed, so that readers never
+    /// see a partially written table
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        // a temporary file of its own, so that processes saving at the same time never write to
+        // the same file
+        static SAVES: AtomicUsize = AtomicUsize::new(0);
+        let tmp = path.with_extension(format!(
+            "{}.{}.tmp",
+            std::process::id(),
+            SAVES.fetch_add(1, Ordering::Relaxed)
+        ));
+        {
+            let mut file = io::BufWriter::new(File::create(&tmp)?);
+            file.write_all(&header(self.len, self.hash))?;
+            file.write_all(&self.data)?;
+            file.into_inner()?.sync_all()?;
+        }
+        fs::rename(&tmp, path)
+    }
+
+    /// returns the number of words (rows) in the table
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// returns `true` if the table was built from an empty list of words
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// returns the pattern seen when guessing the word at index `guess` and the answer is the
+    /// word at index `answer`
+    pub fn get(&self, guess: usize, answer: usize) -> PatternId {
+        PatternId(self.data[guess * self.len + answer])
+    }
+
+    /// returns the patterns of the word at index `guess` against every answer, in word order
+    pub fn row(&self, guess: usize) -> impl Iterator<Item=PatternId> + '_ {
+        self.data[guess * self.len..(guess + 1) * self.len]
+            .iter()
+            .map(|&id| PatternId(id))
+    }
+}
+
+/// builds the header of a cache file for a table of `len` words with the given words `hash`
+fn header(len: usize, hash: u64) -> [u8; HEADER_LEN] {
+    let mut header = [0u8; HEADER_LEN];
+    header[0..8].copy_from_slice(MAGIC);
+    header[8..12].copy_from_slice(&VERSION.to_le_bytes());
+    header[12..16].copy_from_slice(&(WORD_LEN as u32).to_le_bytes());
+    header[16..24].copy_from_slice(&(len as u64).to_le_bytes());
+    header[24..32].copy_from_slice(&hash.to_le_bytes());
+    header
+}
+
+/// computes a 64-bit FNV-1a hash of `words`, in order.
+/// Unlike `std`'s `DefaultHasher`, this hash is stable across Rust releases, so it can be stored
+/// in a cache file
+pub fn words_hash(words: &[&str]) -> u64 {
+    const OFFSET: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+    words
+        .iter()
+        .flat_map(|w| w.bytes().chain(std::iter::once(b'\n')))
+        .fold(OFFSET, |hash, b| (hash ^ b as u64).wrapping_mul(PRIME))
+}
+
+/// returns the default location of the pattern table cache file of `words`, see
+/// `WordList::load_table`. The file is named after the hash of the words, so that every
+/// dictionary keeps a cache file of its own
+pub fn default_cache_path(words: &[&str]) -> PathBuf {
+    std::env::temp_dir().join(format!("wordle_solver_patterns_{:016x}.bin", words_hash(words)))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Correctness, PatternId, PatternTable};
+    use crate::pattern::{default_cache_path, words_hash};
+
+    #[test]
+    fn ids_match_patterns_order() {
+        for (i, mask) in Correctness::patterns().enumerate() {
+            let id = PatternId::from(mask);
+            assert_eq!(id.index(), i);
+            assert_eq!(<[Correctness; 5]>::from(id), mask);
+        }
+    }
+
+    #[test]
+    fn all_green_is_zero() {
+        assert_eq!(PatternId::compute("tares", "tares"), PatternId::CORRECT);
+        assert_eq!(PatternId::compute("tares", "tares").get(), 0);
+    }
+
+    #[test]
+    fn all_gray_is_max() {
+        assert_eq!(PatternId::compute("abcde", "qwxyz").index(), PatternId::COUNT - 1);
+        assert_eq!(PatternId::from_u8(243), None);
+    }
+
+    #[test]
+    fn compute_agrees_with_correctness_compute() {
+        let words = ["aabbb", "aaccc", "ccaac", "caacc", "azzaz", "aaabb", "baccc", "aaddd",
+            "abcde", "eabcd", "aacde", "tares", "brink", "speed", "erase", "eerie"];
+        for answer in words {
+            for guess in words {
+                assert_eq!(
+                    PatternId::compute(answer, guess),
+                    PatternId::from(Correctness::compute(answer, guess)),
+                    "answer {} guess {}", answer, guess
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn table_lookup() {
+        let words = ["tares", "brink", "speed", "erase"];
+        let table = PatternTable::new(&words);
+        assert_eq!(table.len(), 4);
+        for (g, guess) in words.iter().enumerate() {
+            for (a, answer) in words.iter().enumerate() {
+                assert_eq!(table.get(g, a), PatternId::compute(answer, guess));
+            }
+            assert_eq!(table.row(g).nth(g), Some(PatternId::CORRECT));
+        }
+        assert_eq!(
+            table.get(0, 3),
+            PatternId::from(Correctness::try_from_str("wmmmm").unwrap())
+        );
+    }
+
+    #[test]
+    fn cache_round_trip() {
+        let words = ["tares", "brink", "speed", "erase", "eerie"];
+        let path = std::env::temp_dir().join(format!("wordle_pattern_test_{}.bin", std::process::id()));
+        let built = PatternTable::load_or_build(&path, &words).unwrap();
+        let loaded = PatternTable::load(&path, &words).unwrap().expect("cache file is up-to-date");
+        for g in 0..words.len() {
+            assert!(built.row(g).eq(loaded.row(g)));
+        }
+
+        // a changed word list invalidates the cache file
+        let changed = ["tares", "brink", "speed", "erase", "eager"];
+        assert!(PatternTable::load(&path, &changed).unwrap().is_none());
+        assert!(PatternTable::load(&path, &words[..4]).unwrap().is_none());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn every_dictionary_has_its_own_cache_file() {
+        let words = ["tares", "brink", "speed"];
+        assert_eq!(default_cache_path(&words), default_cache_path(&words));
+        assert_ne!(default_cache_path(&words), default_cache_path(&words[..2]));
+    }
+
+    #[test]
+    fn save_replaces_the_cache_file() {
+        let words = ["tares", "brink", "speed", "erase"];
+        let path = std::env::temp_dir().join(format!("wordle_pattern_save_{}.bin", std::process::id()));
+        // tables saved by two threads at once are both complete
+        std::thread::scope(|s| {
+            for _ in 0..2 {
+                s.spawn(|| PatternTable::new(&words).save(&path).unwrap());
+            }
+        });
+        assert!(PatternTable::load(&path, &words).unwrap().is_some());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn hash_depends_on_order() {
+        assert_ne!(words_hash(&["tares", "brink"]), words_hash(&["brink", "tares"]));
+    }
+}
@@ -0,0 +1,189 @@
+//! Priors, the weight of each word's chance of being the answer before any guess is made.
+//!
+//! The guessing algorithms weigh every remaining word by its prior when they compute the
+//! probability of a word being the answer, and the probability of each correctness pattern.
+//! Raw occurrence counts make very common words dominate, while obscure but valid answers get
+//! almost no weight. A sigmoid over the frequency rank of a word, as used by 3Blue1Brown, instead
+//! treats the few thousand most common words as about equally likely and then falls off.
+//! The `fit` module measures the sigmoid's parameters on the past answers of wordle.
+//!
+//! A prior can be saved to and loaded from a prior file, which holds the prior the way `--prior`
+//! takes it on the command line. Blank lines and lines starting with `#` are skipped.
+//!
+pub mod fit;
+
+use std::fmt;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+use anyhow::anyhow;
+use crate::WordList;
+
+/// The prior weight of a word being the answer
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub enum Prior {
+    /// the word's occurrence count
+    #[default]
+    Count,
+    /// every word is equally likely
+    Uniform,
+    /// a logistic function of the word's frequency rank, the most common word has rank zero.
+    /// Words ranked below `midpoint` weigh more than one half, and `width` is the number of ranks
+    /// it takes for the weight to fall by a factor of e around the midpoint
+    Sigmoid { midpoint: f64, width: f64 },
+}
+
+impl Prior {
+    /// a sigmoid prior with roughly the parameters 3Blue1Brown chose, the 3000 most common words
+    /// are likely answers
+    pub const SIGMOID: Prior = Prior::Sigmoid { midpoint: 3000.0, width: 650.0 };
+
+    /// returns the prior weight of a word that occurred `count` times and has frequency `rank`
+    pub fn weight(&self, count: usize, rank: usize) -> f64 {
+        match *self {
+            Prior::Count => count as f64,
+            Prior::Uniform => 1.0,
+            Prior::Sigmoid { midpoint, width } => {
+                1.0 / (1.0 + ((rank as f64 - midpoint) / width).exp())
+            }
+        }
+    }
+
+    /// returns the prior weight of every word of `words`, in list order
+    pub fn weights(&self, words: &WordList) -> Vec<f64> {
+        words
+            .entries()
+            .iter()
+            .zip(words.ranks())
+            .map(|(&(_, count), &rank)| self.weight(count, rank))
+            .collect()
+    }
+
+    /// reads the prior from the prior file at `path`
+    pub fn load(path: &Path) -> Result<Self, anyhow::Error> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("could not read prior from {}: {}", path.display(), e))?;
+        let mut lines = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'));
+        match (lines.next(), lines.next()) {
+            (Some(line), None) => line
+                .parse()
+                .map_err(|e| anyhow!("{}: {}", path.display(), e)),
+            (None, _) => Err(anyhow!("{} does not hold a prior", path.display())),
+            (Some(_), Some(_)) => Err(anyhow!("{} holds more than one prior", path.display())),
+        }
+    }
+
+    /// writes the prior to a prior file at `path`, after a comment line for each of `comments`
+    pub fn save(&self, path: &Path, comments: &[String]) -> io::Result<()> {
+        let mut text = String::new();
+        for comment in comments {
+            text.push_str(&format!("# {}\n", comment));
+        }
+        text.push_str(&format!("{}\n", self));
+        std::fs::write(path, text)
+    }
+}
+
+impl fmt::Display for Prior {
+    /// formats the prior the way `from_str` parses it
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Prior::Count => write!(f, "count"),
+            Prior::Uniform => write!(f, "uniform"),
+            Prior::Sigmoid { midpoint, width } => write!(f, "sigmoid:{}:{}", midpoint, width),
+        }
+    }
+}
+
+impl FromStr for Prior {
+    type Err = anyhow::Error;
+
+    /// parses `count`, `uniform`, `sigmoid` with the default parameters, or
+    /// `sigmoid:<midpoint>:<width>`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "count" => Ok(Prior::Count),
+            "uniform" => Ok(Prior::Uniform),
+            "sigmoid" => Ok(Prior::SIGMOID),
+            s => {
+                let params = s
+                    .strip_prefix("sigmoid:")
+                    .and_then(|params| params.split_once(':'))
+                    .and_then(|(midpoint, width)| Some((midpoint.parse().ok()?, width.parse().ok()?)));
+                match params {
+                    Some((midpoint, width)) if width > 0.0 => Ok(Prior::Sigmoid { midpoint, width }),
+                    _ => Err(anyhow!(
+                        "a prior must be 'count', 'uniform', 'sigmoid' or 'sigmoid:<midpoint>:<width>', got '{}'", s
+                    )),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use crate::WordList;
+    use super::Prior;
+
+    #[test]
+    fn parse_prior() {
+        assert_eq!("count".parse::<Prior>().unwrap(), Prior::Count);
+        assert_eq!("Uniform".parse::<Prior>().unwrap(), Prior::Uniform);
+        assert_eq!("sigmoid".parse::<Prior>().unwrap(), Prior::SIGMOID);
+        assert_eq!(
+            "sigmoid:2500:100.5".parse::<Prior>().unwrap(),
+            Prior::Sigmoid { midpoint: 2500.0, width: 100.5 }
+        );
+        assert!("sigmoid:2500".parse::<Prior>().is_err());
+        assert!("sigmoid:2500:0".parse::<Prior>().is_err());
+        assert!("zipf".parse::<Prior>().is_err());
+        for prior in [Prior::Count, Prior::Uniform, Prior::SIGMOID] {
+            assert_eq!(prior.to_string().parse::<Prior>().unwrap(), prior);
+        }
+    }
+
+    #[test]
+    fn sigmoid_falls_off_with_rank() {
+        let prior = Prior::Sigmoid { midpoint: 100.0, width: 10.0 };
+        assert!((prior.weight(1, 100) - 0.5).abs() < 1e-12);
+        assert!(prior.weight(1, 0) > 0.99);
+        assert!(prior.weight(1, 200) < 0.01);
+        // only the rank matters, not the count
+        assert_eq!(prior.weight(1, 50), prior.weight(1000, 50));
+    }
+
+    #[test]
+    fn weights_follow_the_list() {
+        let words = WordList::dictionary();
+        let counts = Prior::Count.weights(words);
+        assert_eq!(counts.len(), words.len());
+        assert_eq!(counts[0], words.entries()[0].1 as f64);
+
+        // the most common word gets the highest sigmoid weight
+        let sigmoid = Prior::SIGMOID.weights(words);
+        let most_common = words.ranks().iter().position(|&rank| rank == 0).unwrap();
+        assert!(sigmoid.iter().all(|&w| w <= sigmoid[most_common]));
+    }
+
+    #[test]
+    fn save_and_load() {
+        let path = std::env::temp_dir().join(format!("wordle_solver_prior_{}.txt", std::process::id()));
+        let prior = Prior::Sigmoid { midpoint: 2874.125, width: 512.5 };
+        prior.save(&path, &["fitted to 1847 answers".to_string()]).unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(text, "# fitted to 1847 answers\nsigmoid:2874.125:512.5\n");
+        assert_eq!(Prior::load(&path).unwrap(), prior);
+
+        std::fs::write(&path, "# nothing\n\n").unwrap();
+        assert!(Prior::load(&path).is_err());
+        std::fs::write(&path, "count\nuniform\n").unwrap();
+        assert!(Prior::load(&path).is_err());
+        std::fs::remove_file(&path).unwrap();
+        assert!(Prior::load(Path::new("no/such/prior.txt")).is_err());
+    }
+}
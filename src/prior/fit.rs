@@ -0,0 +1,175 @@
+//! Fitting the parameters of a sigmoid `Prior` to the past answers of wordle.
+//!
+//! Every past answer is treated as a word drawn from the dictionary with a probability that is
+//! proportional to its prior weight. The sigmoid's midpoint and width are chosen to maximize the
+//! likelihood of the older answers, the training answers, and the fitted prior is judged by the
+//! likelihood of the most recent answers, the test answers, which the fit never saw.
+//!
+use crate::{Error, WordList};
+use super::Prior;
+
+/// The answers of a list split by date, as indices into the dictionary
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Split {
+    /// the older answers, that a prior is fitted to
+    pub train: Vec<usize>,
+    /// the most recent answers, that a fitted prior is tested on
+    pub test: Vec<usize>,
+}
+
+/// splits `answers`, which are listed in the order wordle used them, into the most recent
+/// `test_fraction` of the answers and the older answers before them. Returns an
+/// `Error::InvalidWord` for an answer that is not in the `dictionary`
+pub fn split(dictionary: &WordList, answers: &WordList, test_fraction: f64) -> Result<Split, Error> {
+    let mut indices = answers
+        .words()
+        .map(|word| dictionary.index_of(word).ok_or_else(|| Error::InvalidWord(word.to_string())))
+        .collect::<Result<Vec<usize>, Error>>()?;
+    let test_len = (indices.len() as f64 * test_fraction.clamp(0.0, 1.0)).round() as usize;
+    let test = indices.split_off(indices.len() - test_len);
+    Ok(Split { train: indices, test })
+}
+
+/// returns the mean natural log of the probability `prior` gives each of the `answers`, indices
+/// into `dictionary`, of being the answer. Higher is better, a uniform prior scores
+/// `-ln(dictionary.len())`
+pub fn log_likelihood(prior: &Prior, dictionary: &WordList, answers: &[usize]) -> f64 {
+    let log_weights: Vec<f64> = dictionary
+        .entries()
+        .iter()
+        .zip(dictionary.ranks())
+        .map(|(&(_, count), &rank)| log_weight(prior, count, rank))
+        .collect();
+    mean_log_probability(&log_weights, answers)
+}
+
+/// finds the sigmoid prior that maximizes the likelihood of the `answers`, indices into
+/// `dictionary`, see `log_likelihood`.
+///
+/// A coarse grid of midpoints and widths is searched first, then the best point of the grid is
+/// refined by a compass search that halves its steps whenever no neighbour is better.
+pub fn fit_sigmoid(dictionary: &WordList, answers: &[usize]) -> Prior {
+    let ranks = dictionary.ranks();
+    let n = dictionary.len().max(1) as f64;
+    // the widths are searched on a log scale, they range from one rank to the whole dictionary
+    let likelihood = |midpoint: f64, log_width: f64| {
+        let prior = Prior::Sigmoid { midpoint, width: log_width.exp() };
+        let log_weights: Vec<f64> = ranks.iter().map(|&rank| log_weight(&prior, 0, rank)).collect();
+        mean_log_probability(&log_weights, answers)
+    };
+
+    const MIDPOINT_STEPS: usize = 64;
+    const WIDTH_STEPS: usize = 48;
+    let mut step = (n / MIDPOINT_STEPS as f64, n.ln() / WIDTH_STEPS as f64);
+    let mut best = (0.0, 0.0, f64::NEG_INFINITY);
+    for i in 0..=MIDPOINT_STEPS {
+        for j in 0..=WIDTH_STEPS {
+            let (midpoint, log_width) = (i as f64 * step.0, j as f64 * step.1);
+            let ll = likelihood(midpoint, log_width);
+            if ll > best.2 {
+                best = (midpoint, log_width, ll);
+            }
+        }
+    }
+
+    // stop once the midpoint is known to a hundredth of a rank
+    while step.0 > 0.01 {
+        let mut moved = false;
+        for (dm, dw) in [(1.0, 0.0), (-1.0, 0.0), (0.0, 1.0), (0.0, -1.0),
+            (1.0, 1.0), (1.0, -1.0), (-1.0, 1.0), (-1.0, -1.0)] {
+            let (midpoint, log_width) = (best.0 + dm * step.0, best.1 + dw * step.1);
+            let ll = likelihood(midpoint, log_width);
+            if ll > best.2 {
+                best = (midpoint, log_width, ll);
+                moved = true;
+            }
+        }
+        if !moved {
+            step = (step.0 / 2.0, step.1 / 2.0);
+        }
+    }
+    // a hundredth of a rank is far more precise than the fit, keep the prior file readable
+    let round = |x: f64| (x * 100.0).round() / 100.0;
+    Prior::Sigmoid { midpoint: round(best.0), width: round(best.1.exp()) }
+}
+
+/// returns the natural log of the weight `prior` gives a word with `count` and `rank`, computed
+/// without overflowing for words far past the sigmoid's midpoint
+fn log_weight(prior: &Prior, count: usize, rank: usize) -> f64 {
+    match *prior {
+        Prior::Count => (count as f64).ln(),
+        Prior::Uniform => 0.0,
+        Prior::Sigmoid { midpoint, width } => {
+            // ln(1 / (1 + e^x)) = -ln(1 + e^x) = -(max(x, 0) + ln(1 + e^-|x|))
+            let x = (rank as f64 - midpoint) / width;
+            -(x.max(0.0) + (-x.abs()).exp().ln_1p())
+        }
+    }
+}
+
+/// returns the mean log probability of the `answers`, where every word's probability is its
+/// weight over the sum of all weights, given the `log_weights` of every word
+fn mean_log_probability(log_weights: &[f64], answers: &[usize]) -> f64 {
+    if answers.is_empty() {
+        return 0.0;
+    }
+    // log of the sum of all weights, scaled by the largest weight to avoid underflow
+    let max = log_weights.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let log_total = max + log_weights.iter().map(|&w| (w - max).exp()).sum::<f64>().ln();
+    answers.iter().map(|&i| log_weights[i] - log_total).sum::<f64>() / answers.len() as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Error, Prior, WordList};
+    use super::{fit_sigmoid, log_likelihood, split};
+
+    /// a list of `n` made up words, the first word is the most common
+    fn list(n: usize) -> &'static WordList {
+        let text: String = (0..n)
+            .map(|i| {
+                let word: String = (0..5).map(|p| (b'a' + (i / 26usize.pow(p) % 26) as u8) as char).collect();
+                format!("{} {}\n", word, 1000 * (n - i))
+            })
+            .collect();
+        Box::leak(Box::new(WordList::parse("words.txt", String::leak(text)).unwrap()))
+    }
+
+    #[test]
+    fn split_keeps_the_most_recent_answers_for_testing() {
+        let dictionary = WordList::dictionary();
+        let answers = WordList::parse("answers.txt", "cigar\nrebut\nsissy\nhumph\nawake\n").unwrap();
+        let s = split(dictionary, &answers, 0.4).unwrap();
+        let index = |w| dictionary.index_of(w).unwrap();
+        assert_eq!(s.train, vec![index("cigar"), index("rebut"), index("sissy")]);
+        assert_eq!(s.test, vec![index("humph"), index("awake")]);
+
+        let answers = WordList::parse("answers.txt", "cigar\nzzzzz\n").unwrap();
+        assert_eq!(split(dictionary, &answers, 0.5), Err(Error::InvalidWord("zzzzz".to_string())));
+    }
+
+    #[test]
+    fn uniform_likelihood() {
+        let words = list(100);
+        let ll = log_likelihood(&Prior::Uniform, words, &[0, 5, 99]);
+        assert!((ll + 100f64.ln()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn fit_finds_where_the_answers_stop() {
+        // the answers are the 50 most common of 400 words
+        let words = list(400);
+        let answers: Vec<usize> = (0..50).collect();
+        let fitted = fit_sigmoid(words, &answers);
+        let Prior::Sigmoid { midpoint, width } = fitted else {
+            panic!("fitted a {:?}", fitted);
+        };
+        assert!((40.0..=60.0).contains(&midpoint), "midpoint {}", midpoint);
+        assert!(width < 10.0, "width {}", width);
+
+        let ll = log_likelihood(&fitted, words, &answers);
+        for prior in [Prior::Count, Prior::Uniform, Prior::Sigmoid { midpoint: 100.0, width: 20.0 }] {
+            assert!(ll > log_likelihood(&prior, words, &answers), "{} fits better", prior);
+        }
+    }
+}
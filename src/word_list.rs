@@ -0,0 +1,264 @@
+//! Lists of five letter words, such as the dictionary of valid guesses and the past answers.
+//!
+//! The lists shipped with the crate are embedded into the binary, but a list can also be loaded
+//! from a file at runtime. Every line of a list file holds a word, optionally followed by its
+//! occurrence count, the number of times it appeared in books. See the `parser` module for the
+//! exact format.
+//!
+//! The guessing algorithms borrow their words from a list for as long as the program runs, so
+//! lists are handed out as `&'static WordList`. A list loaded from a file is leaked to make it
+//! `'static`, which is fine for the handful of lists a program loads.
+//!
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use crate::PatternTable;
+use crate::parser::{self, ParseError};
+
+/// list of all 5 letter words, and their occurrence counts
+const DICTIONARY: &str = include_str!("../dictionary.txt");
+
+/// list of past wordle answers, in the order wordle used them
+const ANSWERS: &str = include_str!("../answers.txt");
+
+/// A word of a `WordList` along with its position in the list, which is also its index
+/// into a `PatternTable` built from the list
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Word {
+    /// index of the word in the list
+    pub index: usize,
+    /// the word
+    pub word: &'static str,
+    /// the prior weight of the word being the answer, its occurrence count unless the
+    /// algorithm was given another `Prior`
+    pub weight: f64,
+}
+
+/// A list of words and their occurrence counts, in the order they were listed
+pub struct WordList {
+    /// every `(word, count)` of the list
+    entries: Vec<(&'static str, usize)>,
+    /// the same words, along with their index
+    words: Vec<Word>,
+    /// maps a word to its index
+    index: HashMap<&'static str, usize>,
+    /// sum of the occurrence counts of all words
+    total: usize,
+    /// the entries sorted from the most to the least common word, computed when first needed
+    by_count: OnceLock<Vec<(&'static str, usize)>>,
+    /// the frequency rank of every word, computed when first needed
+    ranks: OnceLock<Vec<usize>>,
+    /// pre-computed patterns of the words, see `load_table`
+    table: OnceLock<PatternTable>,
+}
+
+impl WordList {
+    /// parses the list of words `text` that was read from `file`, one word per line followed by
+    /// an optional occurrence count, see `parser::parse`
+    pub fn parse(file: &str, text: &'static str) -> Result<Self, ParseError> {
+        parser::parse(file, text).map(Self::from_entries)
+    }
+
+    /// builds a list from `(word, count)` pairs
+    fn from_entries(entries: Vec<(&'static str, usize)>) -> Self {
+        let words = entries
+            .iter()
+            .enumerate()
+            .map(|(index, &(word, count))| Word { index, word, weight: count as f64 })
+            .collect();
+        let index = entries
+            .iter()
+            .enumerate()
+            .map(|(i, &(word, _))| (word, i))
+            .collect();
+        Self {
+            total: entries.iter().map(|&(_, count)| count).sum(),
+            entries,
+            words,
+            index,
+            by_count: OnceLock::new(),
+            ranks: OnceLock::new(),
+            table: OnceLock::new(),
+        }
+    }
+
+    /// reads and parses the list of words in the file at `path`, see `parse`
+    pub fn load(path: &Path) -> Result<&'static WordList, LoadError> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|error| LoadError::Read { file: path.to_path_buf(), error })?;
+        let list = Self::parse(&path.display().to_string(), String::leak(text))
+            .map_err(LoadError::Parse)?;
+        Ok(Box::leak(Box::new(list)))
+    }
+
+    /// loads the list in the file at `path` like `load`, or returns the `default` list if there
+    /// is no path
+    pub fn load_or(path: Option<&Path>, default: &'static WordList) -> Result<&'static WordList, LoadError> {
+        match path {
+            Some(path) => Self::load(path),
+            None => Ok(default),
+        }
+    }
+
+    /// returns the dictionary embedded into the crate, every word that wordle accepts as a guess
+    pub fn dictionary() -> &'static WordList {
+        static LIST: OnceLock<WordList> = OnceLock::new();
+        LIST.get_or_init(|| Self::parse("dictionary.txt", DICTIONARY).expect("the embedded dictionary is valid"))
+    }
+
+    /// returns the past wordle answers embedded into the crate, in the order wordle used them
+    pub fn answers() -> &'static WordList {
+        static LIST: OnceLock<WordList> = OnceLock::new();
+        LIST.get_or_init(|| Self::parse("answers.txt", ANSWERS).expect("the embedded answers are valid"))
+    }
+
+    /// returns the number of words in the list
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// returns `true` if the list has no words
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// returns `true` if `word` is in the list
+    pub fn contains(&self, word: &str) -> bool {
+        self.index.contains_key(word)
+    }
+
+    /// returns the index of `word` in the list
+    pub fn index_of(&self, word: &str) -> Option<usize> {
+        self.index.get(word).copied()
+    }
+
+    /// returns an iterator over the words of the list, in order
+    pub fn words(&self) -> impl Iterator<Item=&'static str> + '_ {
+        self.entries.iter().map(|&(word, _)| word)
+    }
+
+    /// returns every `(word, count)` of the list, in order
+    pub fn entries(&self) -> &[(&'static str, usize)] {
+        &self.entries
+    }
+
+    /// returns the sum of the occurrence counts of all words in the list
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// returns every word of the list along with its index, in order
+    pub(crate) fn indexed(&self) -> &[Word] {
+        &self.words
+    }
+
+    /// returns every `(word, count)` of the list, sorted from the most to the least common word
+    pub(crate) fn by_count(&self) -> &[(&'static str, usize)] {
+        self.by_count.get_or_init(|| {
+            let mut entries = self.entries.clone();
+            entries.sort_unstable_by_key(|&(_, c)| std::cmp::Reverse(c));
+            entries
+        })
+    }
+
+    /// returns the frequency rank of every word of the list, in list order. The most common word
+    /// has rank zero, words with equal counts are ranked in list order
+    pub fn ranks(&self) -> &[usize] {
+        self.ranks.get_or_init(|| {
+            let mut order: Vec<usize> = (0..self.entries.len()).collect();
+            order.sort_by_key(|&i| std::cmp::Reverse(self.entries[i].1));
+            let mut ranks = vec![0; order.len()];
+            for (rank, i) in order.into_iter().enumerate() {
+                ranks[i] = rank;
+            }
+            ranks
+        })
+    }
+
+    /// loads the pattern table of the list from the cache file at `path`, building and saving it
+    /// first if needed. The table is loaded only once, later calls return the same table.
+    pub fn load_table(&self, path: &Path) -> io::Result<&PatternTable> {
+        if let Some(table) = self.table.get() {
+            return Ok(table);
+        }
+        let words: Vec<&str> = self.words().collect();
+        let table = PatternTable::load_or_build(path, &words)?;
+        Ok(self.table.get_or_init(|| table))
+    }
+
+    /// returns the default location of the list's pattern table cache file, in the system temp
+    /// directory, see `pattern::default_cache_path`
+    pub fn default_cache_path(&self) -> PathBuf {
+        let words: Vec<&str> = self.words().collect();
+        crate::pattern::default_cache_path(&words)
+    }
+
+    /// returns the pattern table of the list, if it was loaded by `load_table`
+    pub fn table(&self) -> Option<&PatternTable> {
+        self.table.get()
+    }
+}
+
+/// The reasons a word list file could not be loaded
+#[derive(Debug)]
+pub enum LoadError {
+    /// the file could not be read
+    Read { file: PathBuf, error: io::Error },
+    /// the file has invalid lines
+    Parse(ParseError),
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::Read { file, error } => write!(f, "could not read {}: {}", file.display(), error),
+            LoadError::Parse(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use super::{LoadError, WordList};
+
+    #[test]
+    fn embedded_lists() {
+        let dictionary = WordList::dictionary();
+        assert_eq!(dictionary.len(), 12947);
+        assert_eq!(dictionary.index_of("tares"), dictionary.words().position(|w| w == "tares"));
+        assert!(dictionary.total() > dictionary.len());
+
+        let answers = WordList::answers();
+        assert_eq!(answers.len(), 2309);
+        assert_eq!(answers.total(), answers.len());
+        assert!(answers.words().all(|w| dictionary.contains(w)));
+    }
+
+    #[test]
+    fn counts_are_optional() {
+        let list = WordList::parse("words.txt", "tares 10\nbrink\n\nspeed 3\n").unwrap();
+        assert_eq!(list.entries(), &[("tares", 10), ("brink", 1), ("speed", 3)]);
+        assert_eq!(list.total(), 14);
+        assert_eq!(list.by_count(), &[("tares", 10), ("speed", 3), ("brink", 1)]);
+        assert_eq!(list.ranks(), &[0, 2, 1]);
+    }
+
+    #[test]
+    fn missing_file_is_a_read_error() {
+        let error = WordList::load(Path::new("no/such/words.txt")).err().unwrap();
+        assert!(matches!(error, LoadError::Read { .. }));
+        assert!(error.to_string().starts_with("could not read no/such/words.txt"));
+    }
+
+    #[test]
+    fn load_or_falls_back_to_the_default() {
+        let answers = WordList::load_or(None, WordList::answers()).unwrap();
+        assert!(std::ptr::eq(answers, WordList::answers()));
+        assert!(WordList::load_or(Some(Path::new("no/such/words.txt")), WordList::answers()).is_err());
+    }
+}